@@ -0,0 +1,125 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use kitty_render::camera::Camera;
+use kitty_render::model::{obj::load_obj, primitives};
+use kitty_render::raster::{DepthTest, Evaluation, Triangle};
+use kitty_render::screen::Screen;
+use kitty_render::vector::{transform::Transform, vector2::Vector2, vector3::Vector3};
+
+fn render(c: &mut Criterion) {
+    let mut screen = Screen::new(512, 512);
+    let camera = Camera::default();
+    let mut transform = Transform::new(0.4, 0.2);
+    transform.position = Vector3::new(0., 0., -2.5);
+
+    let monkey = load_obj("models/monkey.obj").expect("Failed to read model data");
+    c.bench_function("render monkey 512x512", |b| {
+        b.iter(|| {
+            screen.clear();
+            screen.render(&camera, black_box(&monkey), &transform);
+        })
+    });
+
+    // Few large triangles, where the time goes into filling pixels
+    let cube = load_obj("models/cube.obj").expect("Failed to read model data");
+    c.bench_function("render cube 512x512", |b| {
+        b.iter(|| {
+            screen.clear();
+            screen.render(&camera, black_box(&cube), &transform);
+        })
+    });
+
+    // The same small cube many times, about two thirds of the copies off screen
+    let transforms: Vec<Transform> = (0..1024)
+        .map(|idx| {
+            let mut transform = Transform::new(idx as f64 * 0.1, 0.3);
+            transform.position = Vector3::new(
+                (idx % 32) as f64 * 0.5 - 8.,
+                (idx / 32) as f64 * 0.5 - 8.,
+                -8.,
+            );
+            transform.scale = Vector3::new(0.05, 0.05, 0.05);
+            transform
+        })
+        .collect();
+    c.bench_function("render 1024 cube instances 512x512", |b| {
+        b.iter(|| {
+            screen.clear();
+            screen.render_instanced(&camera, black_box(&cube), &transforms, &[]);
+        })
+    });
+
+    // Many small triangles, where the time goes into setting them up
+    let sphere = primitives::uv_sphere(1., 128, 64);
+    c.bench_function("render sphere 512x512", |b| {
+        b.iter(|| {
+            screen.clear();
+            screen.render(&camera, black_box(&sphere), &transform);
+        })
+    });
+}
+
+fn scan(c: &mut Criterion) {
+    // Overlapping triangles across a 512x512 depth buffer at 8 different depths, so that many
+    // of their pixels fail the depth test
+    let triangles: Vec<(Triangle, DepthTest)> = (0..32)
+        .map(|idx| {
+            let offset = (idx * 13 % 128) as f64;
+            let corners = [
+                (offset, 0.),
+                (511. - offset, 64. + offset),
+                (offset * 2., 511.),
+            ]
+            .map(|(x, y)| Vector2::new(x, y));
+            let depth = -2. - (idx % 8) as f64 * 0.5;
+            let test = DepthTest {
+                inverse_w: Vector3::new(1., 0.8, 0.6),
+                depths: Vector3::new(depth, depth - 1., depth - 2.),
+                range: -100.0..=-0.1,
+            };
+            (Triangle::new(corners).unwrap(), test)
+        })
+        .collect();
+
+    let mut evaluations = vec![("scalar", Evaluation::Scalar)];
+    #[cfg(target_arch = "x86_64")]
+    evaluations.push(("avx2", Evaluation::Avx2));
+
+    let mut depths = vec![f32::NEG_INFINITY; 512 * 512];
+    for (name, evaluation) in evaluations {
+        c.bench_function(&format!("scan depth 512x512 {name}"), |b| {
+            b.iter(|| {
+                depths.fill(f32::NEG_INFINITY);
+                for (triangle, test) in black_box(&triangles) {
+                    triangle.scan_depth_with(
+                        evaluation,
+                        0..512,
+                        0..512,
+                        &mut depths,
+                        test,
+                        |_, _, _, depth, stored| *stored = depth as f32,
+                    );
+                }
+            })
+        });
+    }
+}
+
+fn vector_ops(c: &mut Criterion) {
+    let vectors: Vec<Vector3<f64>> = (0..1024)
+        .map(|i| Vector3::new(i as f64, (i * 7 % 13) as f64, (i * 3 % 5) as f64))
+        .collect();
+
+    c.bench_function("dot product 1024", |b| {
+        b.iter(|| {
+            black_box(&vectors)
+                .windows(2)
+                .map(|pair| pair[0] * pair[1])
+                .sum::<f64>()
+        })
+    });
+}
+
+criterion_group!(benches, render, scan, vector_ops);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+use std::io::{self, Write};
+
+use kitty_render::camera::Camera;
+use kitty_render::model::{Model, primitives};
+use kitty_render::screen::{Color, Screen};
+use kitty_render::vector::{transform::Transform, vector3::Vector3};
+use termion::event::Key;
+use termion::{input::TermRead, raw::IntoRawMode};
+
+/// Spins every procedural primitive, press 'q' to quit
+fn main() {
+    let mut stdout = io::stdout().into_raw_mode().unwrap();
+    let mut stdin = termion::async_stdin().keys();
+
+    write!(
+        stdout,
+        "{}{}{}",
+        termion::clear::All,
+        termion::cursor::Goto(1, 1),
+        termion::cursor::Hide
+    )
+    .unwrap();
+    stdout.flush().unwrap();
+
+    let mut screen = Screen::new(512, 256);
+    screen.scale(2);
+
+    let models: Vec<Model> = [
+        primitives::uv_sphere(0.7, 24, 12),
+        primitives::icosphere(0.7, 2),
+        primitives::torus(0.55, 0.2, 32, 12),
+        primitives::cylinder(0.5, 1.2, 24),
+        primitives::cone(0.6, 1.2, 24),
+        primitives::plane(1.2, 1.2, 4, 4),
+        primitives::capsule(0.4, 1.4, 24, 6),
+    ]
+    .into_iter()
+    .map(|mut model| {
+        model.face_colors = (0..model.triangles.len())
+            .map(|_| Color::random())
+            .collect();
+        model
+    })
+    .collect();
+
+    let camera = Camera::default();
+    let mut angle: f64 = 0.;
+    loop {
+        if let Some(Ok(Key::Char('q') | Key::Esc | Key::Ctrl('c'))) = stdin.next() {
+            break;
+        }
+
+        // Lay the models out in a grid of 4 columns
+        for (idx, model) in models.iter().enumerate() {
+            let mut transform = Transform::new(angle, 0.4);
+            transform.position = Vector3::new(
+                -3. + 2. * (idx % 4) as f64,
+                1.2 - 2.4 * (idx / 4) as f64,
+                -5.,
+            );
+
+            screen.render(&camera, model, &transform);
+        }
+
+        screen.draw();
+        angle += 0.02;
+    }
+
+    write!(stdout, "{}", termion::cursor::Show).unwrap();
+    stdout.suspend_raw_mode().unwrap();
+    stdout.flush().unwrap();
+    screen.delete_all_images();
+}
@@ -0,0 +1,111 @@
+use std::ops::{Index, IndexMut};
+
+use crate::screen::Color;
+
+/// Image of any kind of value, stored row by row in a single allocation, starting from the top
+/// left corner
+#[derive(Clone, Debug, PartialEq)]
+pub struct Buffer<T> {
+    pub width: usize,
+    pub height: usize,
+    values: Vec<T>,
+}
+
+impl<T: Clone> Buffer<T> {
+    /// Creates a buffer with every value set to `value`
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Buffer {
+            width,
+            height,
+            values: vec![value; width * height],
+        }
+    }
+
+    /// Sets every value to `value`
+    pub fn fill(&mut self, value: T) {
+        self.values.fill(value);
+    }
+}
+
+impl<T> Buffer<T> {
+    /// Value at column `x` of row `y`, `None` outside of the buffer
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.values.get(y * self.width + x)
+    }
+
+    /// Values of row `y`, from left to right
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.values[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Every value, row after row
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+}
+
+impl Buffer<Color> {
+    /// The RGBA bytes of the pixels, row after row, without copying them
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: colors are made of 4 bytes, laid out in order and without padding
+        unsafe {
+            std::slice::from_raw_parts(
+                self.values.as_ptr().cast::<u8>(),
+                self.values.len() * size_of::<Color>(),
+            )
+        }
+    }
+}
+
+/// Value at `(x, y)`
+impl<T> Index<(usize, usize)> for Buffer<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(x < self.width, "Column {x} is outside of the buffer");
+        &self.values[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Buffer<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(x < self.width, "Column {x} is outside of the buffer");
+        &mut self.values[y * self.width + x]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_buffer() {
+        let mut buffer = Buffer::new(3, 2, 0);
+        buffer[(2, 1)] = 5;
+        buffer[(0, 1)] = 4;
+
+        assert_eq!(buffer.row(1), &[4, 0, 5]);
+        assert_eq!(buffer.as_slice(), &[0, 0, 0, 4, 0, 5]);
+        assert_eq!(buffer.get(2, 1), Some(&5));
+        assert_eq!(buffer.get(3, 0), None);
+
+        buffer.fill(1);
+        assert!(buffer.as_slice().iter().all(|&value| value == 1));
+    }
+
+    #[test]
+    fn test_as_bytes() {
+        let mut buffer = Buffer::new(2, 1, Color::default());
+        buffer[(1, 0)] = Color::new(1, 2, 3, 4);
+
+        assert_eq!(buffer.as_bytes(), &[0, 0, 0, 0, 1, 2, 3, 4]);
+    }
+}
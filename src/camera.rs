@@ -0,0 +1,390 @@
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_3};
+
+use crate::{
+    model::bounds::BoundingSphere,
+    vector::{
+        matrix3::Matrix3, matrix4::Matrix4, quaternion::Quaternion, transform::Transform,
+        vector2::Vector2, vector3::Vector3,
+    },
+};
+
+/// Closest to straight up or down that `Camera::orbit` turns, looking exactly along the up
+/// direction leaves the horizontal axis undefined
+const MAX_ORBIT_PITCH: f64 = FRAC_PI_2 - 1e-3;
+
+/// How the view space is flattened onto the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Far objects look smaller, `fov` is the vertical field of view in radians
+    Perspective { fov: f64 },
+    /// Objects keep their size at any distance, `extent` is the height of the visible area in
+    /// world units
+    Orthographic { extent: f64 },
+}
+
+/// Plane splitting space in two, the points in front of it are those where
+/// `normal * point + distance` is positive
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3<f64>,
+    pub distance: f64,
+}
+
+impl Plane {
+    /// Distance of the point from the plane, negative behind it
+    pub fn signed_distance(&self, point: Vector3<f64>) -> f64 {
+        self.normal * point + self.distance
+    }
+}
+
+/// Volume seen by a camera, bounded by six planes facing inwards: left, right, bottom, top,
+/// near and far
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Planes of the volume that `matrix` maps to the [-1, 1] cube of clip space
+    pub fn from_matrix(matrix: &Matrix4) -> Self {
+        let rows = matrix.rows;
+
+        // Each plane compares a coordinate to w, -w <= x is the same as 0 <= w + x
+        let plane = |row: usize, sign: f64| {
+            let [x, y, z, w] = [0, 1, 2, 3].map(|col| rows[3][col] + sign * rows[row][col]);
+            let normal = Vector3::new(x, y, z);
+
+            // Missing planes, like the far one of an infinite projection, have no normal
+            let length = normal.length();
+            if length == 0. {
+                return Plane {
+                    normal,
+                    distance: w,
+                };
+            }
+
+            Plane {
+                normal: normal / length,
+                distance: w / length,
+            }
+        };
+
+        Frustum {
+            planes: [
+                plane(0, 1.),
+                plane(0, -1.),
+                plane(1, 1.),
+                plane(1, -1.),
+                plane(2, 1.),
+                plane(2, -1.),
+            ],
+        }
+    }
+
+    /// Whether any part of the sphere may be visible. Spheres near the corners can pass
+    /// without touching the volume, but visible ones never fail
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+}
+
+/// Point of view the scene is rendered from. Like in glTF the camera looks down its -z axis,
+/// with +y pointing up on the screen and +x to the right
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// Position in world space
+    pub position: Vector3<f64>,
+    pub rotation: Quaternion,
+    pub projection: Projection,
+    /// Distance of the closest visible point
+    pub near: f64,
+    /// Distance of the farthest visible point, can be infinite
+    pub far: f64,
+    /// Width over height of the image, `None` to follow the screen it is rendered to
+    pub aspect: Option<f64>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::perspective(FRAC_PI_3)
+    }
+}
+
+impl Camera {
+    /// Camera at the origin, looking down the -z axis
+    pub fn new(projection: Projection) -> Self {
+        Camera {
+            position: Vector3::default(),
+            rotation: Quaternion::IDENTITY,
+            projection,
+            near: 0.01,
+            far: f64::INFINITY,
+            aspect: None,
+        }
+    }
+
+    pub fn perspective(fov: f64) -> Self {
+        Camera::new(Projection::Perspective { fov })
+    }
+
+    pub fn orthographic(extent: f64) -> Self {
+        Camera::new(Projection::Orthographic { extent })
+    }
+
+    /// Places the camera like a transform, its scale is ignored
+    pub fn with_transform(mut self, transform: &Transform) -> Self {
+        self.position = transform.position;
+        self.rotation = transform.rotation;
+        self
+    }
+
+    /// Direction the camera looks at
+    pub fn forward(&self) -> Vector3<f64> {
+        self.rotation.rotate(Vector3::new(0., 0., -1.))
+    }
+
+    /// Direction pointing right on the screen
+    pub fn right(&self) -> Vector3<f64> {
+        self.rotation.rotate(Vector3::new(1., 0., 0.))
+    }
+
+    /// Direction pointing up on the screen
+    pub fn up(&self) -> Vector3<f64> {
+        self.rotation.rotate(Vector3::new(0., 1., 0.))
+    }
+
+    /// Turns the camera towards `target`, keeping `up` as close as possible to the top of the
+    /// screen. Nothing changes if the camera is already at `target`
+    pub fn look_at(&mut self, target: Vector3<f64>, up: Vector3<f64>) {
+        let forward = (target - self.position).normalize();
+        if forward == Vector3::default() {
+            return;
+        }
+
+        // Looking along `up` any horizontal axis works, pick the one closest to the current one
+        let mut right = forward.cross(&up).normalize();
+        if right == Vector3::default() {
+            right = forward.cross(&self.up()).normalize();
+        }
+        if right == Vector3::default() {
+            right = forward.cross(&self.forward().cross(&self.up())).normalize();
+        }
+
+        let up = right.cross(&forward);
+        self.rotation = Quaternion::from_matrix(&Matrix3::from_columns(right, up, -forward));
+    }
+
+    /// Moves the camera around `target` at the same distance, turning `yaw` radians around the
+    /// vertical axis and `pitch` radians up, and then looks at it. The pitch stops just before
+    /// the camera gets straight above or below the target
+    pub fn orbit(&mut self, target: Vector3<f64>, yaw: f64, pitch: f64) {
+        let offset = self.position - target;
+        let distance = offset.length();
+        if distance == 0. {
+            return;
+        }
+
+        let yaw = offset.x.atan2(offset.z) + yaw;
+        let pitch = ((offset.y / distance).asin() + pitch).clamp(-MAX_ORBIT_PITCH, MAX_ORBIT_PITCH);
+
+        self.position = target
+            + Vector3::new(
+                pitch.cos() * yaw.sin(),
+                pitch.sin(),
+                pitch.cos() * yaw.cos(),
+            ) * distance;
+        self.look_at(target, Vector3::new(0., 1., 0.));
+    }
+
+    /// Width over height of the image, when rendering to a screen of the given size
+    pub fn aspect_ratio(&self, size: &Vector2<f64>) -> f64 {
+        self.aspect.unwrap_or(size.x / size.y)
+    }
+
+    /// Moves a world space point to view space, where the camera is at the origin looking down
+    /// the -z axis
+    pub fn world_to_view(&self, point: Vector3<f64>) -> Vector3<f64> {
+        self.rotation.conjugate().rotate(point - self.position)
+    }
+
+    /// Matrix doing the same as `world_to_view`
+    pub fn view_matrix(&self) -> Matrix4 {
+        Matrix4::from(self.rotation.conjugate().to_matrix())
+            * Matrix4::from_translation(-self.position)
+    }
+
+    /// Matrix from view space to clip space. After dividing by `w` the visible points have all
+    /// coordinates in [-1, 1], with `z` = -1 on the near plane and 1 on the far one
+    pub fn projection_matrix(&self, aspect: f64) -> Matrix4 {
+        let (near, far) = (self.near, self.far);
+
+        match self.projection {
+            Projection::Perspective { fov } => {
+                let focal = 1. / (fov / 2.).tan();
+
+                // Without far plane the depth only approaches 1
+                let depth = match far.is_finite() {
+                    true => [(far + near) / (near - far), 2. * far * near / (near - far)],
+                    false => [-1., -2. * near],
+                };
+
+                Matrix4::new([
+                    [focal / aspect, 0., 0., 0.],
+                    [0., focal, 0., 0.],
+                    [0., 0., depth[0], depth[1]],
+                    [0., 0., -1., 0.],
+                ])
+            }
+            Projection::Orthographic { extent } => {
+                let half_height = extent / 2.;
+
+                // Without far plane every point gets the depth of the near plane
+                let depth = match far.is_finite() {
+                    true => [-2. / (far - near), -(far + near) / (far - near)],
+                    false => [0., -1.],
+                };
+
+                Matrix4::new([
+                    [1. / (half_height * aspect), 0., 0., 0.],
+                    [0., 1. / half_height, 0., 0.],
+                    [0., 0., depth[0], depth[1]],
+                    [0., 0., 0., 1.],
+                ])
+            }
+        }
+    }
+
+    /// World space volume visible with the given aspect ratio
+    pub fn frustum(&self, aspect: f64) -> Frustum {
+        Frustum::from_matrix(&(self.projection_matrix(aspect) * self.view_matrix()))
+    }
+
+    /// Pixels covered by one world unit at `distance` from the camera, on a screen `height`
+    /// pixels tall
+    pub fn pixels_per_unit(&self, distance: f64, height: f64) -> f64 {
+        match self.projection {
+            Projection::Perspective { fov } => height / ((fov / 2.).tan() * 2.) / distance,
+            Projection::Orthographic { extent } => height / extent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vector::vector4::Vector4;
+
+    fn assert_close(a: Vector3<f64>, b: Vector3<f64>) {
+        assert!((a - b).length() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_look_at() {
+        let mut camera = Camera {
+            position: Vector3::new(3., 4., 5.),
+            ..Default::default()
+        };
+
+        let target = Vector3::new(-1., 2., 0.);
+        camera.look_at(target, Vector3::new(0., 1., 0.));
+
+        // The target ends up straight ahead, and the horizon stays level
+        let view = camera.world_to_view(target);
+        assert!(view.x.abs() < 1e-9 && view.y.abs() < 1e-9 && view.z < 0.);
+        assert!(camera.right().y.abs() < 1e-9 && camera.up().y > 0.);
+        assert_close(camera.view_matrix().transform_point(target), view);
+
+        // Looking straight down keeps a valid orientation
+        camera.look_at(
+            camera.position - Vector3::new(0., 1., 0.),
+            Vector3::new(0., 1., 0.),
+        );
+        assert_close(camera.forward(), Vector3::new(0., -1., 0.));
+        assert!((camera.rotation.length() - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_orbit() {
+        let target = Vector3::new(1., 0., -1.);
+        let mut camera = Camera {
+            position: target + Vector3::new(0., 0., 4.),
+            ..Default::default()
+        };
+
+        camera.orbit(target, FRAC_PI_2, 0.);
+        assert_close(camera.position, target + Vector3::new(4., 0., 0.));
+        assert_close(camera.forward(), Vector3::new(-1., 0., 0.));
+
+        // Pitching past the top stops right before it
+        camera.orbit(target, 0., 10.);
+        assert!((camera.position.distance(&target) - 4.).abs() < 1e-9);
+        assert!(camera.position.y > 3.99 && camera.up().y > 0.);
+        assert_close(camera.forward(), (target - camera.position).normalize());
+    }
+
+    #[test]
+    fn test_frustum() {
+        let sphere = |x: f64, y: f64, z: f64| BoundingSphere {
+            center: Vector3::new(x, y, z),
+            radius: 1.,
+        };
+
+        let mut camera = Camera::perspective(FRAC_PI_2);
+        camera.position = Vector3::new(0., 0., 10.);
+        camera.far = 20.;
+        let frustum = camera.frustum(1.);
+
+        assert!(frustum.intersects_sphere(&sphere(0., 0., 0.)));
+        // Touching the right side at a distance of 10
+        assert!(frustum.intersects_sphere(&sphere(10.5, 0., 0.)));
+        assert!(!frustum.intersects_sphere(&sphere(12., 0., 0.)));
+        assert!(!frustum.intersects_sphere(&sphere(0., -12., 0.)));
+        // Behind the camera and past the far plane
+        assert!(!frustum.intersects_sphere(&sphere(0., 0., 12.)));
+        assert!(!frustum.intersects_sphere(&sphere(0., 0., -12.)));
+
+        // Without far plane anything in front is kept
+        camera.far = f64::INFINITY;
+        assert!(
+            camera
+                .frustum(1.)
+                .intersects_sphere(&sphere(0., 0., -1000.))
+        );
+    }
+
+    #[test]
+    fn test_projection() {
+        let project = |camera: &Camera, point: Vector3<f64>| {
+            (camera.projection_matrix(2.) * Vector4::from_vector3(point, 1.)).to_cartesian()
+        };
+
+        let mut camera = Camera::perspective(FRAC_PI_2);
+        camera.near = 1.;
+        camera.far = 10.;
+
+        // The top edge of the field of view, the side edge is twice as wide
+        assert_close(
+            project(&camera, Vector3::new(0., 1., -1.)),
+            Vector3::new(0., 1., -1.),
+        );
+        assert_close(
+            project(&camera, Vector3::new(-4., 0., -2.)),
+            Vector3::new(-1., 0., 1. / 9.),
+        );
+        assert_close(
+            project(&camera, Vector3::new(0., 0., -10.)),
+            Vector3::new(0., 0., 1.),
+        );
+
+        let mut camera = Camera::orthographic(4.);
+        camera.near = 1.;
+        camera.far = 3.;
+        assert_close(
+            project(&camera, Vector3::new(4., -2., -2.)),
+            Vector3::new(1., -1., 0.),
+        );
+        assert_eq!(camera.pixels_per_unit(100., 64.), 16.);
+    }
+}
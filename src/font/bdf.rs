@@ -0,0 +1,208 @@
+use std::{fs, io, str::SplitWhitespace};
+
+use super::{Font, Glyph, unpack_rows};
+
+/// Loads a font in the Glyph Bitmap Distribution Format (BDF)
+pub fn load_bdf(path: &str) -> io::Result<Font> {
+    parse_bdf(&fs::read_to_string(path)?)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Parses the text of a BDF font. Characters without a Unicode encoding are skipped
+fn parse_bdf(content: &str) -> io::Result<Font> {
+    let numbers = |tokens: SplitWhitespace| -> Vec<i64> {
+        tokens.filter_map(|token| token.parse().ok()).collect()
+    };
+
+    // Width, height and offset from the origin of the font, or of the current character
+    let mut font_box: Option<[i64; 4]> = None;
+    let mut ascent: Option<i64> = None;
+    let mut descent: Option<i64> = None;
+
+    let mut encoding: Option<i64> = None;
+    let mut advance: Option<i64> = None;
+    let mut glyph_box: Option<[i64; 4]> = None;
+    let mut glyphs = Vec::new();
+
+    let line_count = content.lines().count();
+    let mut lines = content.lines().enumerate();
+    while let Some((line_idx, line)) = lines.next() {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("FONTBOUNDINGBOX") => font_box = numbers(tokens).try_into().ok(),
+            Some("FONT_ASCENT") => ascent = numbers(tokens).first().copied(),
+            Some("FONT_DESCENT") => descent = numbers(tokens).first().copied(),
+
+            Some("STARTCHAR") => (encoding, advance, glyph_box) = (None, None, None),
+            Some("ENCODING") => encoding = numbers(tokens).first().copied(),
+            Some("DWIDTH") => advance = numbers(tokens).first().copied(),
+            Some("BBX") => glyph_box = numbers(tokens).try_into().ok(),
+
+            Some("BITMAP") => {
+                let [width, height, x_offset, y_offset] = glyph_box
+                    .or(font_box)
+                    .ok_or_else(|| invalid("Character without a bounding box"))?;
+                let (width, height) = match (usize::try_from(width), usize::try_from(height)) {
+                    (Ok(width), Ok(height)) => (width, height),
+                    _ => return Err(invalid("Negative bounding box size")),
+                };
+
+                // Each row is a line, and each byte two digits of it, so larger boxes cannot be
+                // filled by the rest of the file
+                let row_bytes = width.div_ceil(8);
+                if height > line_count - line_idx - 1
+                    || row_bytes
+                        .checked_mul(height)
+                        .is_none_or(|size| size > content.len())
+                {
+                    return Err(invalid("Bounding box larger than the font"));
+                }
+
+                // Rows of hexadecimal bytes, padded to whole bytes. Any other character, including
+                // bytes of non-ASCII ones, is not a digit
+                let digit = |byte: u8| (byte as char).to_digit(16);
+                let mut bytes = Vec::new();
+                for (_, row) in lines.by_ref().take(height) {
+                    let mut row_values: Vec<u8> = row
+                        .trim()
+                        .as_bytes()
+                        .chunks_exact(2)
+                        .map(|pair| Some((digit(pair[0])? << 4 | digit(pair[1])?) as u8))
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| invalid("Invalid bitmap row"))?;
+                    row_values.resize(row_bytes, 0);
+                    bytes.extend(row_values);
+                }
+                let bitmap = unpack_rows(&bytes, width, height)
+                    .ok_or_else(|| invalid("Bitmap shorter than its bounding box"))?;
+
+                // Offsets are from the origin on the baseline, with y going up
+                let character = encoding
+                    .and_then(|encoding| u32::try_from(encoding).ok())
+                    .and_then(char::from_u32);
+                if let Some(character) = character {
+                    let glyph = (bitmap, (x_offset, y_offset + height as i64), advance);
+                    glyphs.push((character, glyph));
+                }
+            }
+
+            Some("ENDFONT") => break,
+            _ => {}
+        }
+    }
+
+    // Lines go from the highest ascent to the lowest descent
+    let [_, font_height, _, font_y_offset] =
+        font_box.ok_or_else(|| invalid("Font without a bounding box"))?;
+    let ascent = ascent.unwrap_or(font_height + font_y_offset);
+    let descent = descent.unwrap_or(-font_y_offset);
+
+    let mut font = Font::new((ascent + descent).max(0) as usize);
+    for (character, (bitmap, (left, top), advance)) in glyphs {
+        let advance = advance.map_or(bitmap.width, |advance| advance.max(0) as usize);
+        let glyph = Glyph {
+            bitmap,
+            offset: (left, ascent - top),
+            advance,
+        };
+        font.insert(character, glyph);
+    }
+
+    Ok(font)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_bdf() {
+        let font = parse_bdf(
+            "
+            STARTFONT 2.1
+            FONT -test-fixed
+            SIZE 8 75 75
+            FONTBOUNDINGBOX 6 9 0 -2
+            STARTPROPERTIES 2
+            FONT_ASCENT 7
+            FONT_DESCENT 2
+            ENDPROPERTIES
+            CHARS 3
+            STARTCHAR A
+            ENCODING 65
+            SWIDTH 500 0
+            DWIDTH 6 0
+            BBX 3 2 1 0
+            BITMAP
+            40
+            A0
+            ENDCHAR
+            STARTCHAR g
+            ENCODING 103
+            DWIDTH 6 0
+            BBX 9 2 0 -2
+            BITMAP
+            8080
+            7F
+            ENDCHAR
+            STARTCHAR unencoded
+            ENCODING -1
+            BBX 1 1 0 0
+            BITMAP
+            80
+            ENDCHAR
+            ENDFONT
+            ",
+        )
+        .unwrap();
+        assert_eq!(font.line_height, 9);
+
+        // Sitting on the baseline, 7 pixels below the top of the line
+        let glyph = font.glyph('A').unwrap();
+        assert_eq!(glyph.offset, (1, 5));
+        assert_eq!(glyph.advance, 6);
+        assert_eq!(glyph.bitmap.row(0), &[false, true, false]);
+        assert_eq!(glyph.bitmap.row(1), &[true, false, true]);
+
+        // Going below the baseline, with a short row padded with zeros
+        let glyph = font.glyph('g').unwrap();
+        assert_eq!(glyph.offset, (0, 7));
+        assert_eq!(
+            glyph.bitmap.row(0),
+            &[true, false, false, false, false, false, false, false, true]
+        );
+        assert_eq!(
+            glyph.bitmap.row(1),
+            &[false, true, true, true, true, true, true, true, false]
+        );
+
+        // Only the encoded characters are kept, and nothing stands in for the others
+        assert_eq!(font.glyph('B'), None);
+        assert_eq!(font.line_width("Ag"), 12);
+    }
+
+    #[test]
+    fn test_invalid_bdf() {
+        let error = parse_bdf("STARTCHAR A\nENCODING 65\nBITMAP\n80\nENDCHAR").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let error = parse_bdf("FONTBOUNDINGBOX 8 8 0 0\nSTARTCHAR A\nBITMAP\nZZ\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // Boxes larger than the file, however large their size
+        for bounding_box in ["BBX 8 3 0 0", "BBX 4000000000000 4000000000000 0 0"] {
+            let content =
+                format!("FONTBOUNDINGBOX 8 8 0 0\nSTARTCHAR A\n{bounding_box}\nBITMAP\n80\n");
+            let error = parse_bdf(&content).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+
+        // Non-ASCII characters are not split in the middle
+        let error = parse_bdf("FONTBOUNDINGBOX 8 8 0 0\nSTARTCHAR A\nBITMAP\naé\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
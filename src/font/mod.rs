@@ -0,0 +1,346 @@
+pub mod bdf;
+pub mod psf;
+
+use std::collections::HashMap;
+
+use crate::buffer::Buffer;
+
+/// Width of the glyphs of the embedded font, in pixels
+const EMBEDDED_WIDTH: usize = 5;
+
+/// Rows of the glyphs of the embedded font, from the top, with the leftmost pixel in the
+/// highest bit. From ' ' to '~'
+#[rustfmt::skip]
+const EMBEDDED_GLYPHS: [[u8; 7]; 95] = [
+    // ' '
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+    // '!'
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100],
+    // '"'
+    [0b01010, 0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000],
+    // '#'
+    [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010],
+    // '$'
+    [0b00100, 0b01111, 0b10100, 0b01110, 0b00101, 0b11110, 0b00100],
+    // '%'
+    [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+    // '&'
+    [0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101],
+    // '\''
+    [0b01100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000],
+    // '('
+    [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+    // ')'
+    [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+    // '*'
+    [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000],
+    // '+'
+    [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000],
+    // ','
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000],
+    // '-'
+    [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+    // '.'
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+    // '/'
+    [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000],
+    // '0'
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+    // '1'
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+    // '2'
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+    // '3'
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+    // '4'
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+    // '5'
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+    // '6'
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+    // '7'
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+    // '8'
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+    // '9'
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+    // ':'
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+    // ';'
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000],
+    // '<'
+    [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010],
+    // '='
+    [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000],
+    // '>'
+    [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000],
+    // '?'
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100],
+    // '@'
+    [0b01110, 0b10001, 0b00001, 0b01101, 0b10101, 0b10101, 0b01110],
+    // 'A'
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001],
+    // 'B'
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+    // 'C'
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+    // 'D'
+    [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+    // 'E'
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+    // 'F'
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+    // 'G'
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+    // 'H'
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+    // 'I'
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+    // 'J'
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+    // 'K'
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+    // 'L'
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+    // 'M'
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+    // 'N'
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+    // 'O'
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+    // 'P'
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+    // 'Q'
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+    // 'R'
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+    // 'S'
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+    // 'T'
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+    // 'U'
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+    // 'V'
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+    // 'W'
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+    // 'X'
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+    // 'Y'
+    [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+    // 'Z'
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+    // '['
+    [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110],
+    // '\\'
+    [0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000],
+    // ']'
+    [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110],
+    // '^'
+    [0b00100, 0b01010, 0b10001, 0b00000, 0b00000, 0b00000, 0b00000],
+    // '_'
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111],
+    // '`'
+    [0b01000, 0b00100, 0b00010, 0b00000, 0b00000, 0b00000, 0b00000],
+    // 'a'
+    [0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111],
+    // 'b'
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110],
+    // 'c'
+    [0b00000, 0b00000, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110],
+    // 'd'
+    [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111],
+    // 'e'
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110],
+    // 'f'
+    [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000],
+    // 'g'
+    [0b00000, 0b01111, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110],
+    // 'h'
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001],
+    // 'i'
+    [0b00100, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110],
+    // 'j'
+    [0b00010, 0b00000, 0b00110, 0b00010, 0b00010, 0b10010, 0b01100],
+    // 'k'
+    [0b10000, 0b10000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010],
+    // 'l'
+    [0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+    // 'm'
+    [0b00000, 0b00000, 0b11010, 0b10101, 0b10101, 0b10001, 0b10001],
+    // 'n'
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001],
+    // 'o'
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110],
+    // 'p'
+    [0b00000, 0b00000, 0b11110, 0b10001, 0b11110, 0b10000, 0b10000],
+    // 'q'
+    [0b00000, 0b00000, 0b01101, 0b10011, 0b01111, 0b00001, 0b00001],
+    // 'r'
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000],
+    // 's'
+    [0b00000, 0b00000, 0b01110, 0b10000, 0b01110, 0b00001, 0b11110],
+    // 't'
+    [0b01000, 0b01000, 0b11100, 0b01000, 0b01000, 0b01001, 0b00110],
+    // 'u'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101],
+    // 'v'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+    // 'w'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10101, 0b10101, 0b01010],
+    // 'x'
+    [0b00000, 0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001],
+    // 'y'
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110],
+    // 'z'
+    [0b00000, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111],
+    // '{'
+    [0b00010, 0b00100, 0b00100, 0b01000, 0b00100, 0b00100, 0b00010],
+    // '|'
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+    // '}'
+    [0b01000, 0b00100, 0b00100, 0b00010, 0b00100, 0b00100, 0b01000],
+    // '~'
+    [0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000],
+];
+
+/// Image of a character
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyph {
+    /// Pixels drawn, from the top left corner
+    pub bitmap: Buffer<bool>,
+    /// Position of the bitmap from the top left corner of the character's place on the line
+    pub offset: (i64, i64),
+    /// Distance from the start of this character to the start of the next one
+    pub advance: usize,
+}
+
+/// Set of bitmap glyphs, drawn on lines `line_height` pixels apart
+#[derive(Clone, Debug, PartialEq)]
+pub struct Font {
+    pub line_height: usize,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl Font {
+    /// Creates a font without any glyph
+    pub fn new(line_height: usize) -> Self {
+        Font {
+            line_height,
+            glyphs: HashMap::new(),
+        }
+    }
+
+    /// Adds the glyph of `character`, replacing the previous one
+    pub fn insert(&mut self, character: char, glyph: Glyph) {
+        self.glyphs.insert(character, glyph);
+    }
+
+    /// Glyph drawn for `character`. Characters without one are drawn as '?', or skipped if the
+    /// font has no '?' either
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// Width and height in pixels of `text`, with a line per '\n'
+    pub fn measure(&self, text: &str) -> (usize, usize) {
+        let width = text
+            .lines()
+            .map(|line| self.line_width(line))
+            .max()
+            .unwrap_or(0);
+
+        (width, text.lines().count().max(1) * self.line_height)
+    }
+
+    /// Sum of the advances of the characters of a line
+    pub fn line_width(&self, line: &str) -> usize {
+        line.chars()
+            .filter_map(|character| self.glyph(character))
+            .map(|glyph| glyph.advance)
+            .sum()
+    }
+}
+
+/// The embedded font, with 5 by 7 pixel glyphs for printable ASCII characters
+impl Default for Font {
+    fn default() -> Self {
+        let mut font = Font::new(EMBEDDED_GLYPHS[0].len() + 1);
+
+        for (character, rows) in (' '..='~').zip(EMBEDDED_GLYPHS) {
+            let mut bitmap = Buffer::new(EMBEDDED_WIDTH, rows.len(), false);
+            for (y, row) in rows.into_iter().enumerate() {
+                for x in 0..EMBEDDED_WIDTH {
+                    bitmap[(x, y)] = row & (1 << (EMBEDDED_WIDTH - 1 - x)) != 0;
+                }
+            }
+
+            // A column between characters
+            let glyph = Glyph {
+                bitmap,
+                offset: (0, 0),
+                advance: EMBEDDED_WIDTH + 1,
+            };
+            font.insert(character, glyph);
+        }
+
+        font
+    }
+}
+
+/// Unpacks rows of bits, each starting on a new byte with the leftmost pixel in the highest bit,
+/// as used by BDF and PSF fonts. `None` if there are not enough bytes
+fn unpack_rows(bytes: &[u8], width: usize, height: usize) -> Option<Buffer<bool>> {
+    let row_bytes = width.div_ceil(8);
+    if bytes.len() < row_bytes * height {
+        return None;
+    }
+
+    let mut bitmap = Buffer::new(width, height, false);
+    for y in 0..height {
+        let row = &bytes[y * row_bytes..(y + 1) * row_bytes];
+        for x in 0..width {
+            bitmap[(x, y)] = row[x / 8] & (0x80 >> (x % 8)) != 0;
+        }
+    }
+
+    Some(bitmap)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_embedded_font() {
+        let font = Font::default();
+        assert_eq!(font.line_height, 8);
+
+        // The stem of the 'T' runs down the middle
+        let glyph = font.glyph('T').unwrap();
+        assert_eq!(glyph.bitmap.row(0), &[true; 5]);
+        for y in 1..7 {
+            assert_eq!(glyph.bitmap.row(y), &[false, false, true, false, false]);
+        }
+
+        // Characters without a glyph are drawn as '?'
+        assert_eq!(font.glyph('\u{e9}'), font.glyph('?'));
+        assert_eq!(font.measure("ab\ncde\u{e9}"), (24, 16));
+        assert_eq!(font.measure(""), (0, 8));
+    }
+
+    #[test]
+    fn test_unpack_rows() {
+        let bitmap = unpack_rows(&[0b1010_0000, 0b1000_0000, 0, 0b0100_0000], 10, 2).unwrap();
+        assert_eq!(
+            bitmap.row(0),
+            &[
+                true, false, true, false, false, false, false, false, true, false
+            ]
+        );
+        assert!(bitmap.row(1)[9]);
+        assert_eq!(unpack_rows(&[0; 3], 10, 2), None);
+    }
+}
@@ -0,0 +1,235 @@
+use std::{fs, io};
+
+use super::{Font, Glyph, unpack_rows};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+
+/// PSF1 fonts with this mode bit have 512 glyphs instead of 256
+const PSF1_MODE_512: u8 = 0x01;
+/// PSF1 fonts with any of these mode bits are followed by a Unicode table
+const PSF1_MODE_HAS_TABLE: u8 = 0x06;
+/// PSF2 fonts with this flag are followed by a Unicode table
+const PSF2_HAS_TABLE: u32 = 0x01;
+
+/// Loads a PC Screen Font (PSF) of version 1 or 2, as used by the Linux console
+pub fn load_psf(path: &str) -> io::Result<Font> {
+    parse_psf(&fs::read(path)?)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Layout of the glyphs of a PSF font
+struct Header {
+    version: u8,
+    size: usize,
+    glyphs: usize,
+    glyph_bytes: usize,
+    width: usize,
+    height: usize,
+    has_table: bool,
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> io::Result<Header> {
+        if bytes.len() >= 4 && bytes[..2] == PSF1_MAGIC {
+            let (mode, height) = (bytes[2], bytes[3]);
+            return Ok(Header {
+                version: 1,
+                size: 4,
+                glyphs: if mode & PSF1_MODE_512 != 0 { 512 } else { 256 },
+                glyph_bytes: height as usize,
+                width: 8,
+                height: height as usize,
+                has_table: mode & PSF1_MODE_HAS_TABLE != 0,
+            });
+        }
+
+        if bytes.len() < 32 || bytes[..4] != PSF2_MAGIC {
+            return Err(invalid("Not a PSF font"));
+        }
+        let field = |idx: usize| {
+            let value = &bytes[4 * idx..4 * idx + 4];
+            u32::from_le_bytes([value[0], value[1], value[2], value[3]])
+        };
+
+        Ok(Header {
+            version: 2,
+            size: field(2) as usize,
+            glyphs: field(4) as usize,
+            glyph_bytes: field(5) as usize,
+            width: field(7) as usize,
+            height: field(6) as usize,
+            has_table: field(3) & PSF2_HAS_TABLE != 0,
+        })
+    }
+}
+
+/// Parses the bytes of a PSF font. Without a Unicode table, glyphs are assigned to the
+/// characters with the same code
+fn parse_psf(bytes: &[u8]) -> io::Result<Font> {
+    let header = Header::parse(bytes)?;
+    if header.width == 0 || header.height == 0 {
+        return Err(invalid("Glyphs without pixels"));
+    }
+    if header.glyph_bytes < header.width.div_ceil(8) * header.height {
+        return Err(invalid("Glyphs smaller than their size"));
+    }
+
+    // The glyph count is checked against the length before anything is allocated for them
+    let end = header
+        .glyphs
+        .checked_mul(header.glyph_bytes)
+        .and_then(|glyph_bytes| glyph_bytes.checked_add(header.size))
+        .filter(|&end| end <= bytes.len())
+        .ok_or_else(|| invalid("Font shorter than its glyphs"))?;
+    let bitmaps = bytes[header.size..end]
+        .chunks_exact(header.glyph_bytes)
+        .map(|glyph| unpack_rows(glyph, header.width, header.height))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid("Font shorter than its glyphs"))?;
+
+    // Characters of each glyph, each list ending with a terminator. Sequences of combining
+    // characters follow a separator, and are skipped
+    let table = &bytes[end..];
+    let characters: Vec<Vec<char>> = match (header.has_table, header.version) {
+        (false, _) => (0..header.glyphs as u32)
+            .map(|code| char::from_u32(code).into_iter().collect())
+            .collect(),
+        (true, 1) => {
+            let values: Vec<u16> = table
+                .chunks_exact(2)
+                .map(|value| u16::from_le_bytes([value[0], value[1]]))
+                .collect();
+            values
+                .split(|&value| value == 0xffff)
+                .map(|entry| {
+                    let singles = entry.split(|&value| value == 0xfffe).next();
+                    singles
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|&value| char::from_u32(value as u32))
+                        .collect()
+                })
+                .collect()
+        }
+        (true, _) => table
+            .split(|&byte| byte == 0xff)
+            .map(|entry| {
+                let singles = entry.split(|&byte| byte == 0xfe).next();
+                std::str::from_utf8(singles.unwrap_or_default())
+                    .map(|singles| singles.chars().collect())
+                    .unwrap_or_default()
+            })
+            .collect(),
+    };
+
+    let mut font = Font::new(header.height);
+    for (bitmap, characters) in bitmaps.into_iter().zip(characters) {
+        for character in characters {
+            let glyph = Glyph {
+                bitmap: bitmap.clone(),
+                offset: (0, 0),
+                advance: header.width,
+            };
+            font.insert(character, glyph);
+        }
+    }
+
+    Ok(font)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_psf1() {
+        // 256 glyphs of 8 by 2 pixels, with a Unicode table
+        let mut bytes = vec![PSF1_MAGIC[0], PSF1_MAGIC[1], 0x02, 2];
+        bytes.extend((0..=255).flat_map(|code: u8| [code, !code]));
+        for code in 0..256u16 {
+            // The first glyph is also the replacement character, then a combining sequence
+            let characters: &[u16] = match code {
+                0 => &[0xfffd, 0xfffe, 0x41, 0x300],
+                _ => &[code],
+            };
+            for value in characters.iter().chain(&[0xffff]) {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+
+        let font = parse_psf(&bytes).unwrap();
+        assert_eq!(font.line_height, 2);
+        let glyph = font.glyph('\u{fffd}').unwrap();
+        assert_eq!(glyph.bitmap.row(1), &[true; 8]);
+        assert_eq!(glyph.advance, 8);
+
+        let glyph = font.glyph('A').unwrap();
+        assert_eq!(
+            glyph.bitmap.row(0),
+            &[false, true, false, false, false, false, false, true]
+        );
+
+        // Sequences of combining characters are left out
+        assert_eq!(font.glyph('\u{300}'), font.glyph('?'));
+    }
+
+    #[test]
+    fn test_parse_psf2() {
+        // 2 glyphs of 10 by 1 pixels, without a Unicode table
+        let mut bytes = PSF2_MAGIC.to_vec();
+        for field in [0, 32, 0, 2, 2, 1, 10] {
+            bytes.extend(u32::to_le_bytes(field));
+        }
+        bytes.extend([0xff, 0xc0, 0x80, 0x40]);
+
+        let font = parse_psf(&bytes).unwrap();
+        let glyph = font.glyph('\u{1}').unwrap();
+        assert_eq!(glyph.bitmap.row(0)[7..], [false, false, true]);
+        assert_eq!(glyph.advance, 10);
+        assert_eq!(font.glyph('\u{2}'), None);
+
+        // A table gives the glyphs to other characters
+        bytes[12] = PSF2_HAS_TABLE as u8;
+        bytes.extend("é".bytes().chain([0xff]).chain("ab".bytes()).chain([0xff]));
+        let font = parse_psf(&bytes).unwrap();
+        assert_eq!(font.glyph('é').unwrap().bitmap.row(0), &[true; 10]);
+        assert_eq!(font.glyph('\u{1}'), None);
+        assert_eq!(font.glyph('a'), font.glyph('b'));
+
+        bytes.truncate(35);
+        assert!(parse_psf(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_invalid_psf() {
+        let psf2 = |fields: [u32; 7]| {
+            let mut bytes = PSF2_MAGIC.to_vec();
+            bytes.extend(fields.iter().flat_map(|field| field.to_le_bytes()));
+            parse_psf(&bytes).unwrap_err().kind()
+        };
+
+        // Glyphs without pixels
+        assert_eq!(
+            psf2([0, 32, 0, 0xffff_ffff, 0, 0, 0]),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(psf2([0, 32, 0, 1, 1, 0, 8]), io::ErrorKind::InvalidData);
+
+        // More glyphs than the file holds, however large their count and size
+        assert_eq!(
+            psf2([0, 32, 0, 0xffff_ffff, 1, 1, 8]),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            psf2([0, 0xffff_ffff, 0, 0xffff_ffff, 0xffff_ffff, 1, 8]),
+            io::ErrorKind::InvalidData
+        );
+
+        let error = parse_psf(&[PSF1_MAGIC[0], PSF1_MAGIC[1], 0, 0]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
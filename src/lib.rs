@@ -0,0 +1,9 @@
+pub mod buffer;
+pub mod camera;
+pub mod font;
+pub mod model;
+pub mod raster;
+pub mod scene;
+pub mod screen;
+pub mod texture;
+pub mod vector;
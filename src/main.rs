@@ -1,19 +1,27 @@
-mod model;
-mod screen;
-mod vector;
-
-use model::{Model, load_obj};
+use kitty_render::camera::{Camera, Projection};
+use kitty_render::font::Font;
+use kitty_render::model::{
+    Model,
+    bounds::BoundingSphere,
+    gltf::load_gltf,
+    obj::{load_obj, load_obj_normalized},
+};
+use kitty_render::scene::{Node, Scene};
+use kitty_render::screen::{AntiAliasing, Color, CullMode, Filter, PolygonMode, Screen, TextStyle};
+use kitty_render::vector::{transform::Transform, vector3::Vector3};
 use nix::libc::EXIT_SUCCESS;
-use screen::{Color, Screen};
+use std::env;
+use std::f64::consts::PI;
 use std::io;
 use std::io::Write;
 use std::process::exit;
+use std::time::Instant;
 use termion::event::Key;
 use termion::raw::RawTerminal;
 use termion::{input::TermRead, raw::IntoRawMode};
-use vector::transform::Transform;
 
-use crate::vector::vector3::Vector3;
+/// Number of simplified versions of the loaded OBJ models
+const LOD_LEVELS: usize = 3;
 
 fn main() {
     // Terminal setup -------------------
@@ -41,28 +49,97 @@ fn main() {
     screen.scale(2);
     // let mut screen = Screen::new_fullscreen();
 
-    // Load cube model
-    let monkey_points = load_obj("models/monkey.obj").expect("Failed to read model data");
-    let cube_points = load_obj("models/cube.obj").expect("Failed to read model data");
+    let mut camera = Camera::default();
+
+    // Assign a random color to each triangle, and simplified versions to draw when far away
+    let random_colors = |mut model: Model| {
+        model.face_colors = (0..model.triangles.len())
+            .map(|_| Color::random())
+            .collect();
+        model.with_lods(LOD_LEVELS)
+    };
+
+    let mut frame_on_load = true;
+    let scene = match env::args().nth(1) {
+        // Load the glTF scene passed as an argument, viewed from its first camera
+        Some(path) if path.ends_with(".gltf") || path.ends_with(".glb") => {
+            let scene = load_gltf(&path).expect("Failed to read glTF scene");
+
+            if let Some(scene_camera) = scene.cameras.first() {
+                camera = *scene_camera;
+                frame_on_load = false;
+            }
+
+            scene
+        }
+
+        // Load the OBJ model passed as an argument
+        Some(path) => {
+            let model = load_obj_normalized(&path).expect("Failed to read model data");
 
-    // Assign a random color to each triangle
-    let triangle_colors: Vec<Color> = (0..monkey_points.windows(3).count())
-        .map(|_| Color::random())
-        .collect();
+            Scene::new().with_node(Node::default().with_model(random_colors(model)))
+        }
+
+        // Place the monkey next to a smaller, tilted cube
+        None => {
+            let monkey = load_obj("models/monkey.obj").expect("Failed to read model data");
+            let cube = load_obj("models/cube.obj").expect("Failed to read model data");
+
+            let mut monkey_transform = Transform::new(0.3, 0.);
+            monkey_transform.position = Vector3::new(-1.2, 0., 0.);
+
+            let mut cube_transform = Transform::new(0.6, 0.4);
+            cube_transform.position = Vector3::new(1.5, 0., 0.);
+            cube_transform.scale = Vector3::new(0.5, 0.5, 0.5);
+
+            Scene::new()
+                .with_node(
+                    Node::new(monkey_transform)
+                        .with_name("monkey")
+                        .with_model(random_colors(monkey)),
+                )
+                .with_node(
+                    Node::new(cube_transform)
+                        .with_name("cube")
+                        .with_model(random_colors(cube)),
+                )
+        }
+    };
 
-    let monkey = Model::new(monkey_points, triangle_colors.clone());
-    let cube = Model::new(cube_points, triangle_colors);
+    // Bounding sphere of the whole scene
+    let bounds = scene.aabb().bounding_sphere();
 
-    let mut transform = Transform::default();
-    transform.position = Vector3::new(0., 0., -2.5);
+    if frame_on_load {
+        screen.frame(&bounds, &mut camera);
+    }
+    let home = camera;
+    let mut anti_aliasing = AntiAliasing::None;
+    let font = Font::default();
+    let mut last_frame = Instant::now();
     // Init -----------------------------
 
     // Loop -----------------------------
     loop {
-        handle_input(&mut stdin, &mut stdout, &mut screen, &mut transform);
+        handle_input(
+            &mut stdin,
+            &mut stdout,
+            &mut screen,
+            &mut camera,
+            &home,
+            &bounds,
+            &mut anti_aliasing,
+        );
+
+        screen.render_scene(&camera, &scene);
 
-        screen.render(&monkey, &transform);
-        screen.render(&cube, &transform);
+        // Frame rate in the top left corner
+        let fps = 1. / last_frame.elapsed().as_secs_f64();
+        last_frame = Instant::now();
+        let style = TextStyle {
+            scale: 2,
+            ..Default::default()
+        };
+        screen.draw_text(&font, &format!("{fps:.0} fps"), (4, 4), style);
 
         screen.draw();
     }
@@ -73,7 +150,10 @@ fn handle_input(
     stdin: &mut termion::input::Keys<termion::AsyncReader>,
     stdout: &mut RawTerminal<io::Stdout>,
     screen: &mut Screen,
-    transform: &mut Transform,
+    camera: &mut Camera,
+    home: &Camera,
+    bounds: &BoundingSphere,
+    anti_aliasing: &mut AntiAliasing,
 ) {
     // Read input (if any)
     let input = stdin.next();
@@ -99,34 +179,84 @@ fn handle_input(
             }
 
             // Position
-            Key::Char('w') => transform.position += Vector3::new(0., 0., 0.1),
-            Key::Char('W') => transform.position += Vector3::new(0., 0., 0.5),
-            Key::Char('s') => transform.position += Vector3::new(0., 0., -0.1),
-            Key::Char('S') => transform.position += Vector3::new(0., 0., -0.5),
-            Key::Char('a') => transform.position += Vector3::new(-0.1, 0., 0.),
-            Key::Char('A') => transform.position += Vector3::new(-0.5, 0., 0.),
-            Key::Char('d') => transform.position += Vector3::new(0.1, 0., 0.),
-            Key::Char('D') => transform.position += Vector3::new(0.5, 0., 0.),
-
-            // Rotation
-            Key::Left => transform.yaw += 0.1,
-            Key::Right => transform.yaw -= 0.1,
-            Key::Up => transform.pitch += 0.1,
-            Key::Down => transform.pitch -= 0.1,
+            Key::Char('w') => camera.position += camera.forward() * 0.1,
+            Key::Char('W') => camera.position += camera.forward() * 0.5,
+            Key::Char('s') => camera.position -= camera.forward() * 0.1,
+            Key::Char('S') => camera.position -= camera.forward() * 0.5,
+            Key::Char('a') => camera.position -= camera.right() * 0.1,
+            Key::Char('A') => camera.position -= camera.right() * 0.5,
+            Key::Char('d') => camera.position += camera.right() * 0.1,
+            Key::Char('D') => camera.position += camera.right() * 0.5,
+
+            // Rotation around the scene
+            Key::Left => camera.orbit(bounds.center, -0.1, 0.),
+            Key::Right => camera.orbit(bounds.center, 0.1, 0.),
+            Key::Up => camera.orbit(bounds.center, 0., 0.1),
+            Key::Down => camera.orbit(bounds.center, 0., -0.1),
 
             // Fov
-            Key::Char('q') => screen.fov -= 0.01,
-            Key::Char('e') => screen.fov += 0.01,
-            Key::Char('Q') => screen.fov -= 0.05,
-            Key::Char('E') => screen.fov += 0.05,
-
-            // Reset transformation
-            Key::Char('r') => {
-                transform.yaw = 0.0;
-                transform.pitch = 0.0;
-                transform.position = Vector3::default();
+            Key::Char('q') => zoom(camera, -0.01),
+            Key::Char('e') => zoom(camera, 0.01),
+            Key::Char('Q') => zoom(camera, -0.05),
+            Key::Char('E') => zoom(camera, 0.05),
+
+            // Switch between perspective and orthographic projection
+            Key::Char('p') => {
+                camera.projection = match camera.projection {
+                    Projection::Perspective { .. } => Projection::Orthographic { extent: 1. },
+                    Projection::Orthographic { .. } => Camera::default().projection,
+                };
+                screen.frame(bounds, camera);
+            }
+
+            // Cycle through the faces that are skipped
+            Key::Char('c') => {
+                screen.cull_mode = match screen.cull_mode {
+                    CullMode::Back => CullMode::None,
+                    CullMode::None => CullMode::Front,
+                    CullMode::Front => CullMode::Back,
+                }
+            }
+
+            // Cycle through the anti-aliasing modes
+            Key::Char('m') => {
+                *anti_aliasing = match anti_aliasing {
+                    AntiAliasing::None => AntiAliasing::Multisampling { samples: 4 },
+                    AntiAliasing::Multisampling { .. } => AntiAliasing::Supersampling {
+                        factor: 2,
+                        filter: Filter::Tent,
+                    },
+                    AntiAliasing::Supersampling { .. } => AntiAliasing::None,
+                };
+                screen.anti_aliasing(*anti_aliasing);
             }
+
+            // Cycle through the ways of drawing the triangles
+            Key::Char('l') => {
+                screen.polygon_mode = match screen.polygon_mode {
+                    PolygonMode::Fill => PolygonMode::Wireframe,
+                    PolygonMode::Wireframe => PolygonMode::Points,
+                    PolygonMode::Points => PolygonMode::HiddenLine,
+                    PolygonMode::HiddenLine => PolygonMode::FillAndWireframe,
+                    PolygonMode::FillAndWireframe => PolygonMode::Fill,
+                }
+            }
+
+            // Fit the scene in the screen
+            Key::Char('f') => screen.frame(bounds, camera),
+
+            // Reset the camera
+            Key::Char('r') => *camera = *home,
             _ => {}
         }
     }
 }
+
+/// Widens the field of view, or the visible area of an orthographic camera, by `amount` radians
+/// or a fraction of its size
+fn zoom(camera: &mut Camera, amount: f64) {
+    match &mut camera.projection {
+        Projection::Perspective { fov } => *fov = (*fov + amount).clamp(0.01, PI - 0.01),
+        Projection::Orthographic { extent } => *extent *= 1. + amount,
+    }
+}
@@ -0,0 +1,139 @@
+use crate::vector::{matrix4::Matrix4, vector3::Vector3};
+
+/// Axis aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Aabb {
+    pub min: Vector3<f64>,
+    pub max: Vector3<f64>,
+}
+
+/// Sphere that contains every vertex of a model
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct BoundingSphere {
+    pub center: Vector3<f64>,
+    pub radius: f64,
+}
+
+impl Aabb {
+    /// Smallest box containing all the points, or an empty box at the origin if there are none
+    pub fn from_points(points: &[Vector3<f64>]) -> Self {
+        let Some(&first) = points.first() else {
+            return Aabb::default();
+        };
+
+        points.iter().fold(
+            Aabb {
+                min: first,
+                max: first,
+            },
+            |aabb, &point| {
+                aabb.union(&Aabb {
+                    min: point,
+                    max: point,
+                })
+            },
+        )
+    }
+
+    /// Smallest box containing both boxes
+    pub fn union(&self, other: &Aabb) -> Self {
+        Aabb {
+            min: self.min.min(&other.min),
+            max: self.max.max(&other.max),
+        }
+    }
+
+    pub fn center(&self) -> Vector3<f64> {
+        (self.min + self.max) / 2.
+    }
+
+    pub fn size(&self) -> Vector3<f64> {
+        self.max - self.min
+    }
+
+    /// Box around this one after moving it with `matrix`
+    pub fn transform(&self, matrix: &Matrix4) -> Self {
+        let corners: Vec<Vector3<f64>> = (0..8)
+            .map(|corner| {
+                let pick = |bit: usize, min: f64, max: f64| match corner & bit {
+                    0 => min,
+                    _ => max,
+                };
+                matrix.transform_point(Vector3::new(
+                    pick(1, self.min.x, self.max.x),
+                    pick(2, self.min.y, self.max.y),
+                    pick(4, self.min.z, self.max.z),
+                ))
+            })
+            .collect();
+
+        Aabb::from_points(&corners)
+    }
+
+    /// Sphere passing through the corners of the box
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere {
+            center: self.center(),
+            radius: (self.size() / 2.).length(),
+        }
+    }
+}
+
+impl BoundingSphere {
+    /// Sphere centered in the middle of the bounding box of the points, reaching the farthest one
+    pub fn from_points(points: &[Vector3<f64>]) -> Self {
+        let center = Aabb::from_points(points).center();
+
+        let radius = points
+            .iter()
+            .map(|point| point.distance(&center))
+            .fold(0., f64::max);
+
+        BoundingSphere { center, radius }
+    }
+
+    /// Sphere around this one after moving it with `matrix`. The radius grows with the longest
+    /// axis, which is exact unless the matrix shears
+    pub fn transform(&self, matrix: &Matrix4) -> Self {
+        let linear = matrix.linear();
+        let scale = (0..3)
+            .map(|axis| linear.column(axis).length())
+            .fold(0., f64::max);
+
+        BoundingSphere {
+            center: matrix.transform_point(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_aabb() {
+        let aabb = Aabb::from_points(&[
+            Vector3::new(1., -2., 0.),
+            Vector3::new(-1., 4., 2.),
+            Vector3::new(0., 0., -2.),
+        ]);
+
+        assert_eq!(aabb.min, Vector3::new(-1., -2., -2.));
+        assert_eq!(aabb.max, Vector3::new(1., 4., 2.));
+        assert_eq!(aabb.center(), Vector3::new(0., 1., 0.));
+        assert_eq!(Aabb::from_points(&[]), Aabb::default());
+    }
+
+    #[test]
+    fn test_bounding_sphere() {
+        let sphere = BoundingSphere::from_points(&[
+            Vector3::new(1., 1., 1.),
+            Vector3::new(3., 1., 1.),
+            Vector3::new(2., 2., 1.),
+        ]);
+
+        assert_eq!(sphere.center, Vector3::new(2., 1.5, 1.));
+        assert_eq!(sphere.radius, 1.25f64.sqrt());
+    }
+}
@@ -0,0 +1,371 @@
+use std::sync::Arc;
+
+use gltf::{Document, Primitive, buffer, camera, image, material::AlphaMode, mesh::Mode};
+
+use crate::{
+    camera::{Camera, Projection},
+    scene::{Material, Node, Scene},
+    screen::Color,
+    texture::Texture,
+    vector::{
+        matrix4::Matrix4, quaternion::Quaternion, transform::Transform, vector2::Vector2,
+        vector3::Vector3,
+    },
+};
+
+use super::Model;
+
+/// Loads a `.gltf` or `.glb` file, along with the buffers and images it references.
+/// Only embedded data URIs and local files are read. Each primitive becomes a model in the
+/// space of its node, with the material of the primitive baked into its colors and texture.
+/// Blended primitives also get a transparent node material
+pub fn load_gltf(path: &str) -> gltf::Result<Scene> {
+    let (document, buffers, images) = gltf::import(path)?;
+    Ok(build_scene(&document, &buffers, &images))
+}
+
+fn build_scene(document: &Document, buffers: &[buffer::Data], images: &[image::Data]) -> Scene {
+    let textures: Vec<Option<Arc<Texture>>> = images.iter().map(image_to_texture).collect();
+
+    // Nodes using the same mesh share its models
+    let meshes: Vec<Vec<(Arc<Model>, Option<Material>)>> = document
+        .meshes()
+        .map(|mesh| {
+            mesh.primitives()
+                .filter_map(|primitive| {
+                    let model = primitive_to_model(&primitive, buffers, &textures)?;
+                    let material = (primitive.material().alpha_mode() == AlphaMode::Blend)
+                        .then(|| Material::transparent(Color::new(0xff, 0xff, 0xff, 0xff)));
+                    Some((Arc::new(model), material))
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut scene = Scene::new();
+
+    // Files without scenes are drawn starting from every node that is not a child
+    let roots: Vec<gltf::Node> = match document.default_scene().or(document.scenes().next()) {
+        Some(gltf_scene) => gltf_scene.nodes().collect(),
+        None => document
+            .nodes()
+            .filter(|node| {
+                !document
+                    .nodes()
+                    .any(|parent| parent.children().any(|child| child.index() == node.index()))
+            })
+            .collect(),
+    };
+
+    for node in roots {
+        let node = visit_node(&node, &Matrix4::IDENTITY, &meshes, &mut scene.cameras);
+        scene.nodes.push(node);
+    }
+
+    scene
+}
+
+/// Converts a node and its children, collecting their cameras in world space
+fn visit_node(
+    node: &gltf::Node,
+    parent: &Matrix4,
+    meshes: &[Vec<(Arc<Model>, Option<Material>)>],
+    cameras: &mut Vec<Camera>,
+) -> Node {
+    let (translation, [x, y, z, w], scale) = node.transform().decomposed();
+    let transform = Transform {
+        rotation: Quaternion::new(w as f64, x as f64, y as f64, z as f64),
+        position: Vector3::new(
+            translation[0] as f64,
+            translation[1] as f64,
+            translation[2] as f64,
+        ),
+        scale: Vector3::new(scale[0] as f64, scale[1] as f64, scale[2] as f64),
+    };
+    let world = *parent * transform.to_matrix();
+
+    let mut result = Node::new(transform).with_name(node.name().unwrap_or_default());
+
+    // A node holds a single model, the other primitives go in children at the same place.
+    // Materials are not inherited in glTF, so blended primitives always go in children
+    if let Some(mesh) = node.mesh() {
+        for (idx, (model, material)) in meshes[mesh.index()].iter().enumerate() {
+            match (idx, material) {
+                (0, None) => result.model = Some(model.clone()),
+                _ => {
+                    let mut child = Node::new(Transform::default()).with_model(model.clone());
+                    child.material = material.clone();
+                    result.children.push(child);
+                }
+            }
+        }
+    }
+
+    if let Some(camera) = node.camera() {
+        cameras.push(gltf_camera(&camera, &world));
+    }
+
+    for child in node.children() {
+        let child = visit_node(&child, &world, meshes, cameras);
+        result.children.push(child);
+    }
+
+    result
+}
+
+/// Converts a triangle primitive to a model
+fn primitive_to_model(
+    primitive: &Primitive,
+    buffers: &[buffer::Data],
+    textures: &[Option<Arc<Texture>>],
+) -> Option<Model> {
+    if primitive.mode() != Mode::Triangles {
+        return None;
+    }
+
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()].0));
+
+    let vertices: Vec<Vector3<f64>> = reader
+        .read_positions()?
+        .map(|[x, y, z]| Vector3::new(x as f64, y as f64, z as f64))
+        .collect();
+
+    let indices: Vec<usize> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().map(|idx| idx as usize).collect(),
+        None => (0..vertices.len()).collect(),
+    };
+    // Indices past the vertices would only fail once the model is drawn
+    if indices.iter().any(|&idx| idx >= vertices.len()) {
+        return None;
+    }
+    let triangles: Vec<[usize; 3]> = indices
+        .chunks_exact(3)
+        .map(|corners| [corners[0], corners[1], corners[2]])
+        .collect();
+
+    let pbr = primitive.material().pbr_metallic_roughness();
+    let [red, green, blue, alpha] = pbr.base_color_factor().map(|c| (c * 255.).round() as u8);
+    let face_colors = vec![Color::new(red, green, blue, alpha); triangles.len()];
+
+    let mut model = Model::new(vertices, triangles, face_colors);
+
+    // Attach the base color texture only if the primitive has matching coordinates, one for
+    // each vertex
+    if let Some(info) = pbr.base_color_texture() {
+        let texture = textures[info.texture().source().index()].clone();
+
+        if let (Some(texture), Some(tex_coords)) =
+            (texture, reader.read_tex_coords(info.tex_coord()))
+        {
+            let uvs: Vec<Vector2<f64>> = tex_coords
+                .into_f32()
+                .map(|[u, v]| Vector2::new(u as f64, v as f64))
+                .collect();
+
+            if uvs.len() == model.vertices.len() {
+                model = model.with_texture(uvs, texture);
+            }
+        }
+    }
+
+    // Primitives without indices repeat the shared vertices
+    model.deduplicate();
+
+    Some(model)
+}
+
+/// Converts a glTF camera placed at `world`, ignoring the scale of its node
+fn gltf_camera(camera: &camera::Camera, world: &Matrix4) -> Camera {
+    let result = match camera.projection() {
+        camera::Projection::Perspective(perspective) => {
+            let mut result = Camera::new(Projection::Perspective {
+                fov: perspective.yfov() as f64,
+            });
+            result.near = perspective.znear() as f64;
+            result.far = perspective.zfar().map_or(f64::INFINITY, f64::from);
+            result.aspect = perspective.aspect_ratio().map(f64::from);
+            result
+        }
+        camera::Projection::Orthographic(orthographic) => {
+            // The magnifications are half the size of the visible area
+            let mut result = Camera::new(Projection::Orthographic {
+                extent: 2. * orthographic.ymag() as f64,
+            });
+            result.near = orthographic.znear() as f64;
+            result.far = orthographic.zfar() as f64;
+            result.aspect = Some((orthographic.xmag() / orthographic.ymag()) as f64);
+            result
+        }
+    };
+
+    result.with_transform(&Transform::from_matrix(world))
+}
+
+/// Converts a decoded glTF image to a texture, floating point images are not supported
+fn image_to_texture(image: &image::Data) -> Option<Arc<Texture>> {
+    let (channels, depth) = match image.format {
+        image::Format::R8 => (1, 1),
+        image::Format::R8G8 => (2, 1),
+        image::Format::R8G8B8 => (3, 1),
+        image::Format::R8G8B8A8 => (4, 1),
+        image::Format::R16 => (1, 2),
+        image::Format::R16G16 => (2, 2),
+        image::Format::R16G16B16 => (3, 2),
+        image::Format::R16G16B16A16 => (4, 2),
+        _ => return None,
+    };
+
+    let pixels = image
+        .pixels
+        .chunks_exact(channels * depth)
+        .map(|texel| {
+            // Keep the most significant byte of 16 bit channels
+            let channel = |i: usize| match depth {
+                1 => texel[i],
+                _ => (u16::from_ne_bytes([texel[2 * i], texel[2 * i + 1]]) >> 8) as u8,
+            };
+
+            match channels {
+                1 => Color::new(channel(0), channel(0), channel(0), 0xff),
+                2 => Color::new(channel(0), channel(0), channel(0), channel(1)),
+                3 => Color::new(channel(0), channel(1), channel(2), 0xff),
+                _ => Color::new(channel(0), channel(1), channel(2), channel(3)),
+            }
+        })
+        .collect();
+
+    Some(Arc::new(Texture::new(
+        image.width as usize,
+        image.height as usize,
+        pixels,
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(a: Vector3<f64>, b: Vector3<f64>) {
+        let diff = a - b;
+        assert!(
+            diff.x.abs() < 1e-6 && diff.y.abs() < 1e-6 && diff.z.abs() < 1e-6,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse_gltf_mesh() {
+        let scene = load_gltf("models/triangle.gltf").expect("Failed to read glTF scene");
+
+        let mut models = Vec::new();
+        scene.walk(|node, world, _| {
+            if let Some(model) = &node.model {
+                models.push((*world, model.clone()));
+            }
+        });
+
+        assert_eq!(models.len(), 1);
+        let (world, model) = &models[0];
+
+        // The mesh stays in the space of its node, which is scaled inside a moved parent
+        assert_eq!(model.triangles, vec![[0, 1, 2]]);
+        assert_eq!(
+            model.vertices,
+            vec![
+                Vector3::new(0., 0., 0.),
+                Vector3::new(1., 0., 0.),
+                Vector3::new(0., 1., 0.)
+            ]
+        );
+        assert_close(
+            world.transform_point(Vector3::new(1., 0., 0.)),
+            Vector3::new(2., 0., -2.),
+        );
+        assert_eq!(model.face_colors, vec![Color::new(0xff, 0x80, 0, 0xff)]);
+        assert_eq!(
+            model.uvs,
+            vec![
+                Vector2::new(0., 0.),
+                Vector2::new(1., 0.),
+                Vector2::new(0., 1.)
+            ]
+        );
+
+        let texture = model.texture.as_ref().expect("Missing base color texture");
+        assert_eq!((texture.width, texture.height), (2, 2));
+        assert_eq!(
+            texture.sample(Vector2::new(0.75, 0.75)),
+            Color::new(0xff, 0xff, 0xff, 0xff)
+        );
+    }
+
+    #[test]
+    fn test_invalid_gltf_mesh() {
+        let source = std::fs::read_to_string("models/triangle.gltf").unwrap();
+        let base = std::path::Path::new("models");
+        let load = |source: &str| {
+            let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(source.as_bytes()).unwrap();
+            let buffers = gltf::import_buffers(&document, Some(base), blob).unwrap();
+            let images = gltf::import_images(&document, Some(base), &buffers).unwrap();
+            let mut models = Vec::new();
+            build_scene(&document, &buffers, &images).walk(|node, _, _| {
+                models.extend(node.model.clone());
+            });
+            models
+        };
+
+        // Texture coordinates for fewer vertices than the mesh has leave it untextured
+        let short = source.replace(
+            r#""count": 3, "type": "VEC2""#,
+            r#""count": 2, "type": "VEC2""#,
+        );
+        let models = load(&short);
+        assert_eq!(models.len(), 1);
+        assert!(models[0].texture.is_none());
+
+        // Primitives with indices past their vertices are skipped, with 0, 1 and 2 or 5 as u16
+        for (indices, count) in [("AAABAAIA", 1), ("AAABAAUA", 0)] {
+            let indexed = source
+                .replace(r#""TEXCOORD_0": 1 },"#, r#""TEXCOORD_0": 1 }, "indices": 2,"#)
+                .replace(
+                    r#""type": "VEC2" }"#,
+                    r#""type": "VEC2" },
+                    { "bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR" }"#,
+                )
+                .replace(
+                    r#""byteLength": 24 }"#,
+                    r#""byteLength": 24 },
+                    { "buffer": 1, "byteOffset": 0, "byteLength": 6 }"#,
+                )
+                .replace(
+                    "}],\n    \"bufferViews\"",
+                    &format!(
+                        r#"}}, {{ "byteLength": 6, "uri": "data:application/octet-stream;base64,{indices}" }}],
+                        "bufferViews""#
+                    ),
+                );
+            assert_eq!(load(&indexed).len(), count);
+        }
+    }
+
+    #[test]
+    fn test_parse_gltf_camera() {
+        let scene = load_gltf("models/triangle.gltf").expect("Failed to read glTF scene");
+
+        assert_eq!(scene.cameras.len(), 1);
+        let camera = &scene.cameras[0];
+
+        assert_eq!(
+            camera.projection,
+            Projection::Perspective { fov: 0.8f32 as f64 }
+        );
+        assert_eq!((camera.near, camera.far), (0.1f32 as f64, f64::INFINITY));
+        assert_close(camera.position, Vector3::new(5., 0., 0.));
+
+        // The world origin ends up right in front of the camera
+        assert_close(
+            camera.world_to_view(Vector3::default()),
+            Vector3::new(0., 0., -5.),
+        );
+    }
+}
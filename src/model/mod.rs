@@ -0,0 +1,249 @@
+pub mod bounds;
+pub mod gltf;
+pub mod obj;
+pub mod primitives;
+pub mod simplify;
+pub mod subdivide;
+
+use crate::{
+    model::bounds::{Aabb, BoundingSphere},
+    screen::Color,
+    texture::Texture,
+    vector::{vector2::Vector2, vector3::Vector3},
+};
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, OnceLock},
+};
+
+#[derive(Clone)]
+pub struct Model {
+    /// Unique vertex positions
+    pub vertices: Vec<Vector3<f64>>,
+    /// Indices into `vertices` of the corners of each triangle
+    pub triangles: Vec<[usize; 3]>,
+    /// Color of each triangle
+    pub face_colors: Vec<Color>,
+    /// Polygons the model is made of, as ranges of consecutive triangles split in a fan around
+    /// the first corner. Empty if each triangle is a face by itself
+    pub faces: Vec<Range<usize>>,
+
+    /// Normal of each vertex, empty if the model has none
+    pub normals: Vec<Vector3<f64>>,
+    /// Texture coordinates of each vertex, starting from the top left corner of the texture.
+    /// Empty if the model has none
+    pub uvs: Vec<Vector2<f64>>,
+    /// Texture multiplied with the face colors
+    pub texture: Option<Arc<Texture>>,
+
+    /// Named parts of the model
+    pub groups: Vec<Group>,
+
+    /// Simplified versions of the model, from the most to the least detailed
+    pub lods: Vec<Model>,
+
+    /// Bounding sphere of the vertices, kept once it is first needed
+    bounds: OnceLock<BoundingSphere>,
+}
+
+/// Named range of consecutive triangles of a model
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub triangles: Range<usize>,
+}
+
+impl Model {
+    pub fn new(
+        vertices: Vec<Vector3<f64>>,
+        triangles: Vec<[usize; 3]>,
+        face_colors: Vec<Color>,
+    ) -> Self {
+        Model {
+            vertices,
+            triangles,
+            face_colors,
+            faces: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            texture: None,
+            groups: Vec::new(),
+            lods: Vec::new(),
+            bounds: OnceLock::new(),
+        }
+    }
+
+    /// Adds a texture sampled with the given per-vertex texture coordinates
+    pub fn with_texture(mut self, uvs: Vec<Vector2<f64>>, texture: Arc<Texture>) -> Self {
+        self.uvs = uvs;
+        self.texture = Some(texture);
+        self
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(&self.vertices)
+    }
+
+    /// Sphere around the vertices. It is computed on the first call only, so culling a model
+    /// does not go through its vertices each time it is drawn. Call [`Model::update_bounds`]
+    /// after moving the vertices
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        *self
+            .bounds
+            .get_or_init(|| BoundingSphere::from_points(&self.vertices))
+    }
+
+    /// Forgets the bounding sphere, which is computed again from the vertices when needed
+    pub fn update_bounds(&mut self) {
+        self.bounds = OnceLock::new();
+    }
+
+    /// Corners of each polygon, along with the range of triangles it was split into
+    pub fn polygons(&self) -> impl Iterator<Item = (Range<usize>, Vec<usize>)> + '_ {
+        let faces = match self.faces.is_empty() {
+            true => (0..self.triangles.len()).map(|idx| idx..idx + 1).collect(),
+            false => self.faces.clone(),
+        };
+
+        faces.into_iter().map(|face| {
+            let mut corners = self.triangles[face.start].to_vec();
+            corners.extend(
+                self.triangles[face.start + 1..face.end]
+                    .iter()
+                    .map(|t| t[2]),
+            );
+
+            (face, corners)
+        })
+    }
+
+    /// Moves the model to the origin and scales it to fit in a sphere of radius 1
+    pub fn normalize(&mut self) {
+        let sphere = self.bounding_sphere();
+        if sphere.radius == 0. {
+            return;
+        }
+
+        for vertex in self.vertices.iter_mut() {
+            *vertex = (*vertex - sphere.center) / sphere.radius;
+        }
+        self.update_bounds();
+    }
+
+    /// Index of the position of each vertex among the distinct positions, which are returned
+    /// too. Vertices split by a seam in the normals or texture coordinates share the same one
+    fn weld_positions(&self) -> (Vec<usize>, Vec<Vector3<f64>>) {
+        let mut welded: HashMap<[u64; 3], usize> = HashMap::new();
+        let mut positions = Vec::new();
+
+        let points_of = self
+            .vertices
+            .iter()
+            .map(|&vertex| {
+                let key = [vertex.x, vertex.y, vertex.z].map(|c| (c + 0.).to_bits());
+                *welded.entry(key).or_insert_with(|| {
+                    positions.push(vertex);
+                    positions.len() - 1
+                })
+            })
+            .collect();
+
+        (points_of, positions)
+    }
+
+    /// Merges the vertices that share the same position, normal and texture coordinates
+    pub fn deduplicate(&mut self) {
+        let mut unique: HashMap<[u64; 8], usize> = HashMap::new();
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+
+        for (idx, &vertex) in self.vertices.iter().enumerate() {
+            let normal = self.normals.get(idx).copied().unwrap_or_default();
+            let uv = self.uvs.get(idx).copied().unwrap_or_default();
+
+            // Adding 0 turns -0 into 0, so that both have the same bits
+            let key = [
+                vertex.x, vertex.y, vertex.z, normal.x, normal.y, normal.z, uv.x, uv.y,
+            ]
+            .map(|c| (c + 0.).to_bits());
+
+            let new_idx = *unique.entry(key).or_insert_with(|| {
+                vertices.push(vertex);
+                if !self.normals.is_empty() {
+                    normals.push(normal);
+                }
+                if !self.uvs.is_empty() {
+                    uvs.push(uv);
+                }
+
+                vertices.len() - 1
+            });
+            remap.push(new_idx);
+        }
+
+        for triangle in self.triangles.iter_mut() {
+            *triangle = triangle.map(|idx| remap[idx]);
+        }
+
+        self.vertices = vertices;
+        self.normals = normals;
+        self.uvs = uvs;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_deduplicate() {
+        let points = vec![
+            Vector3::new(0., 0., 0.),
+            Vector3::new(1., 0., 0.),
+            Vector3::new(0., 1., 0.),
+            Vector3::new(1., 0., 0.),
+            Vector3::new(1., 1., 0.),
+            Vector3::new(-0., 1., 0.),
+        ];
+        let mut model = Model::new(
+            points,
+            vec![[0, 1, 2], [3, 4, 5]],
+            vec![Color::default(); 2],
+        );
+
+        model.deduplicate();
+
+        assert_eq!(model.vertices.len(), 4);
+        assert_eq!(model.triangles, vec![[0, 1, 2], [1, 3, 2]]);
+    }
+
+    #[test]
+    fn test_bounding_sphere() {
+        let mut model = Model::new(
+            vec![Vector3::new(1., 2., 3.), Vector3::new(3., 2., 3.)],
+            Vec::new(),
+            Vec::new(),
+        );
+        let sphere = model.bounding_sphere();
+        assert_eq!(
+            (sphere.center, sphere.radius),
+            (Vector3::new(2., 2., 3.), 1.)
+        );
+
+        // Kept until it is updated
+        model.vertices[1] = Vector3::new(5., 2., 3.);
+        assert_eq!(model.bounding_sphere(), sphere);
+        model.update_bounds();
+        assert_eq!(model.bounding_sphere().radius, 2.);
+
+        model.normalize();
+        let sphere = model.bounding_sphere();
+        assert_eq!(
+            (sphere.center, sphere.radius),
+            (Vector3::new(0., 0., 0.), 1.)
+        );
+    }
+}
@@ -0,0 +1,565 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    ops::Range,
+    path::Path,
+};
+
+use crate::{
+    screen::Color,
+    vector::{vector2::Vector2, vector3::Vector3},
+};
+
+use super::{Group, Model};
+
+pub fn load_obj(path: &str) -> std::io::Result<Model> {
+    let mut file = File::open(path)?;
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+
+    // Material libraries are next to the model. Missing ones leave the faces white, unless
+    // their material names hold a color
+    let mut materials = HashMap::new();
+    let libraries = content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("mtllib "))
+        .flat_map(|names| names.split_whitespace());
+    for library in libraries {
+        let library_path = Path::new(path).with_file_name(library);
+        match std::fs::read_to_string(library_path) {
+            Ok(library) => materials.extend(parse_mtl(&library)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(parse_obj(&content, &materials))
+}
+
+/// Loads an OBJ model, centered at the origin and scaled to fit in a sphere of radius 1
+pub fn load_obj_normalized(path: &str) -> std::io::Result<Model> {
+    let mut model = load_obj(path)?;
+    model.normalize();
+    Ok(model)
+}
+
+/// Saves the model as an OBJ file, along with a material library with the same name (`.mtl`)
+/// holding the face colors
+pub fn save_obj(model: &Model, path: &str) -> io::Result<()> {
+    let obj_path = Path::new(path);
+    let mtl_path = obj_path.with_extension("mtl");
+    let library = mtl_path.file_name().and_then(|name| name.to_str());
+
+    write_obj(model, &mut BufWriter::new(File::create(obj_path)?), library)?;
+    write_mtl(model, &mut BufWriter::new(File::create(&mtl_path)?))
+}
+
+/// Writes the model in the OBJ format. If a material library is given, the faces reference the
+/// materials written by `write_mtl`
+pub fn write_obj<W>(model: &Model, writer: &mut W, material_library: Option<&str>) -> io::Result<()>
+where
+    W: Write,
+{
+    if let Some(library) = material_library {
+        writeln!(writer, "mtllib {library}")?;
+    }
+
+    for vertex in &model.vertices {
+        writeln!(writer, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+    }
+
+    // OBJ texture coordinates start from the bottom left corner
+    for uv in &model.uvs {
+        writeln!(writer, "vt {} {}", uv.x, 1. - uv.y)?;
+    }
+
+    for normal in &model.normals {
+        writeln!(writer, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+    }
+
+    // Polygons are written whole, unless a group or a color change splits their triangles
+    let faces = model.polygons().flat_map(|(face, corners)| {
+        let split = face.clone().skip(1).any(|idx| {
+            model.face_colors[idx] != model.face_colors[face.start]
+                || model
+                    .groups
+                    .iter()
+                    .any(|group| group.triangles.start == idx || group.triangles.end == idx)
+        });
+
+        match split {
+            true => face
+                .map(|idx| (idx, model.triangles[idx].to_vec()))
+                .collect(),
+            false => vec![(face.start, corners)],
+        }
+    });
+
+    let mut material = None;
+    for (triangle_idx, corners) in faces {
+        if let Some(group) = model
+            .groups
+            .iter()
+            .find(|group| group.triangles.start == triangle_idx)
+        {
+            writeln!(writer, "g {}", group.name)?;
+        } else if model
+            .groups
+            .iter()
+            .any(|group| group.triangles.end == triangle_idx)
+        {
+            // Leave the previous group
+            writeln!(writer, "g")?;
+        }
+
+        let color = model.face_colors[triangle_idx];
+        if material_library.is_some() && material != Some(color) {
+            writeln!(writer, "usemtl {}", material_name(color))?;
+            material = Some(color);
+        }
+
+        // Vertices, texture coordinates and normals share the same indices, starting from 1
+        let corners: Vec<String> = corners
+            .iter()
+            .map(|idx| {
+                let idx = idx + 1;
+                match (model.uvs.is_empty(), model.normals.is_empty()) {
+                    (true, true) => format!("{idx}"),
+                    (false, true) => format!("{idx}/{idx}"),
+                    (true, false) => format!("{idx}//{idx}"),
+                    (false, false) => format!("{idx}/{idx}/{idx}"),
+                }
+            })
+            .collect();
+        writeln!(writer, "f {}", corners.join(" "))?;
+    }
+
+    writer.flush()
+}
+
+/// Writes a material for each face color of the model
+pub fn write_mtl<W>(model: &Model, writer: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    // In order of first use, keyed by their RGBA bytes
+    let mut written: HashSet<[u8; 4]> = HashSet::new();
+    let colors = model
+        .face_colors
+        .iter()
+        .copied()
+        .filter(|color| written.insert([color.red, color.green, color.blue, color.alpha]));
+
+    for color in colors {
+        writeln!(writer, "newmtl {}", material_name(color))?;
+        writeln!(
+            writer,
+            "Kd {} {} {}",
+            color.red as f64 / 255.,
+            color.green as f64 / 255.,
+            color.blue as f64 / 255.
+        )?;
+        writeln!(writer, "d {}", color.alpha as f64 / 255.)?;
+        writeln!(writer)?;
+    }
+
+    writer.flush()
+}
+
+fn material_name(color: Color) -> String {
+    format!(
+        "color_{:02x}{:02x}{:02x}{:02x}",
+        color.red, color.green, color.blue, color.alpha
+    )
+}
+
+/// Color of a material, from its library or from a name written by `write_obj`
+fn material_color(name: &str, materials: &HashMap<String, Color>) -> Option<Color> {
+    if let Some(&color) = materials.get(name) {
+        return Some(color);
+    }
+
+    let hex = name.strip_prefix("color_").filter(|hex| hex.len() == 8)?;
+    let [red, green, blue, alpha] = u32::from_str_radix(hex, 16).ok()?.to_be_bytes();
+    Some(Color::new(red, green, blue, alpha))
+}
+
+/// Reads the diffuse color and the opacity of each material of an MTL library
+fn parse_mtl(content: &str) -> HashMap<String, Color> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Color)> = None;
+    let channel = |value: Option<&str>| {
+        let value: f64 = value.and_then(|value| value.parse().ok()).unwrap_or(1.);
+        (value.clamp(0., 1.) * 255.).round() as u8
+    };
+
+    for line in content.lines() {
+        let mut tokens = line.split_whitespace();
+
+        match (tokens.next(), &mut current) {
+            (Some("newmtl"), _) => {
+                let name = tokens.collect::<Vec<&str>>().join(" ");
+                materials.extend(current.replace((name, Color::new(0xff, 0xff, 0xff, 0xff))));
+            }
+            (Some("Kd"), Some((_, color))) => {
+                color.red = channel(tokens.next());
+                color.green = channel(tokens.next());
+                color.blue = channel(tokens.next());
+            }
+            (Some("d"), Some((_, color))) => color.alpha = channel(tokens.next()),
+            (Some("Tr"), Some((_, color))) => color.alpha = 255 - channel(tokens.next()),
+            _ => {}
+        }
+    }
+    materials.extend(current);
+
+    materials
+}
+
+/// Parses the text of an OBJ model, with the colors of the materials its faces use. Faces
+/// without a known material are white
+fn parse_obj(content: &str, materials: &HashMap<String, Color>) -> Model {
+    let mut positions: Vec<Vector3<f64>> = Vec::new();
+    let mut tex_coords: Vec<Vector2<f64>> = Vec::new();
+    let mut normals: Vec<Vector3<f64>> = Vec::new();
+
+    // Each distinct combination of position, texture coordinate and normal is a vertex
+    let mut corners: Vec<(usize, Option<usize>, Option<usize>)> = Vec::new();
+    let mut corner_indices: HashMap<(usize, Option<usize>, Option<usize>), usize> = HashMap::new();
+
+    let mut triangles: Vec<[usize; 3]> = Vec::new();
+    let mut faces: Vec<Range<usize>> = Vec::new();
+    let mut groups: Vec<Group> = Vec::new();
+
+    let white = Color::new(0xff, 0xff, 0xff, 0xff);
+    let mut color = white;
+    let mut face_colors: Vec<Color> = Vec::new();
+
+    content.trim().lines().for_each(|line| {
+        let mut tokens = line.split_whitespace();
+        let values = |tokens: std::str::SplitWhitespace| -> Vec<f64> {
+            tokens.filter_map(|val| val.parse().ok()).collect()
+        };
+
+        match tokens.next() {
+            Some("v") => {
+                let vec3 = values(tokens);
+                positions.push(Vector3::new(vec3[0], vec3[1], vec3[2]));
+            }
+
+            // OBJ texture coordinates start from the bottom left corner
+            Some("vt") => {
+                let vec2 = values(tokens);
+                tex_coords.push(Vector2::new(vec2[0], 1. - vec2.get(1).unwrap_or(&0.)));
+            }
+
+            Some("vn") => {
+                let vec3 = values(tokens);
+                normals.push(Vector3::new(vec3[0], vec3[1], vec3[2]));
+            }
+
+            Some("usemtl") => {
+                let name = tokens.collect::<Vec<&str>>().join(" ");
+                color = material_color(&name, materials).unwrap_or(white);
+            }
+
+            Some("o" | "g") => {
+                if let Some(group) = groups.last_mut() {
+                    group.triangles.end = triangles.len();
+                }
+
+                // Nameless groups only close the previous one
+                let name = tokens.collect::<Vec<&str>>().join(" ");
+                if !name.is_empty() {
+                    groups.push(Group {
+                        name,
+                        triangles: triangles.len()..triangles.len(),
+                    });
+                }
+            }
+
+            Some("f") => {
+                // Each group of indices is `vertex/texture/normal`, where the last two are
+                // optional. Indices start from 1, or count backwards from the end if negative
+                let face: Vec<usize> = tokens
+                    .map(|group| {
+                        let mut indices = group.split('/');
+                        let mut next_index = |len: usize| {
+                            let idx: isize = indices.next()?.parse().ok()?;
+                            let idx = if idx < 0 { len as isize + idx } else { idx - 1 };
+                            (0..len as isize).contains(&idx).then_some(idx as usize)
+                        };
+
+                        let corner = (
+                            next_index(positions.len()).expect("Failed to read face data"),
+                            next_index(tex_coords.len()),
+                            next_index(normals.len()),
+                        );
+
+                        *corner_indices.entry(corner).or_insert_with(|| {
+                            corners.push(corner);
+                            corners.len() - 1
+                        })
+                    })
+                    .collect();
+
+                // Split polygons in a fan of triangles around the first vertex
+                let first = triangles.len();
+                for i in 2..face.len() {
+                    triangles.push([face[0], face[i - 1], face[i]]);
+                }
+
+                if triangles.len() > first {
+                    faces.push(first..triangles.len());
+                }
+                face_colors.resize(triangles.len(), color);
+            }
+
+            _ => {}
+        }
+    });
+
+    if let Some(group) = groups.last_mut() {
+        group.triangles.end = triangles.len();
+    }
+    groups.retain(|group| !group.triangles.is_empty());
+
+    let vertices = corners.iter().map(|&(idx, _, _)| positions[idx]).collect();
+
+    let mut model = Model::new(vertices, triangles, face_colors);
+    model.faces = faces;
+    model.groups = groups;
+
+    // Corners missing some attributes get the default values
+    if corners.iter().any(|(_, uv, _)| uv.is_some()) {
+        model.uvs = corners
+            .iter()
+            .map(|(_, uv, _)| uv.map(|idx| tex_coords[idx]).unwrap_or_default())
+            .collect();
+    }
+    if corners.iter().any(|(_, _, normal)| normal.is_some()) {
+        model.normals = corners
+            .iter()
+            .map(|(_, _, normal)| normal.map(|idx| normals[idx]).unwrap_or_default())
+            .collect();
+    }
+
+    model.deduplicate();
+    model
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Expands the triangles of a model to their corner positions
+    fn triangle_points(model: &Model) -> Vec<Vector3<f64>> {
+        model
+            .triangles
+            .iter()
+            .flat_map(|triangle| triangle.map(|idx| model.vertices[idx]))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_obj() {
+        let model = parse_obj(
+            "
+            v 1.000000 1.000000 -1.000000
+            v 1.000000 -1.000000 -1.000000
+            v 1.000000 1.000000 1.000000
+            v 1.000000 -1.000000 1.000000
+            v -1.000000 1.000000 -1.000000
+            v -1.000000 -1.000000 -1.000000
+            v -1.000000 1.000000 1.000000
+            v -1.000000 -1.000000 1.000000
+            f 1/1/1 5/2/1 7/3/1 3/4/1
+            f 4/5/2 3/4/2 7/6/2 8/7/2
+            f 8/8/3 7/9/3 5/10/3 6/11/3
+            f 6/12/4 2/13/4 4/5/4 8/14/4
+            f 2/13/5 1/1/5 3/4/5 4/5/5
+            f 6/11/6 5/10/6 1/1/6 2/13/6
+            ",
+            &HashMap::new(),
+        );
+
+        let expected_vertices = [
+            Vector3::new(1.000000, 1.000000, -1.000000),
+            Vector3::new(1.000000, -1.000000, -1.000000),
+            Vector3::new(1.000000, 1.000000, 1.000000),
+            Vector3::new(1.000000, -1.000000, 1.000000),
+            Vector3::new(-1.000000, 1.000000, -1.000000),
+            Vector3::new(-1.000000, -1.000000, -1.000000),
+            Vector3::new(-1.000000, 1.000000, 1.000000),
+            Vector3::new(-1.000000, -1.000000, 1.000000),
+        ];
+
+        let expected_vertices = vec![
+            expected_vertices[0],
+            expected_vertices[4],
+            expected_vertices[6],
+            expected_vertices[0],
+            expected_vertices[6],
+            expected_vertices[2],
+            expected_vertices[3],
+            expected_vertices[2],
+            expected_vertices[6],
+            expected_vertices[3],
+            expected_vertices[6],
+            expected_vertices[7],
+            expected_vertices[7],
+            expected_vertices[6],
+            expected_vertices[4],
+            expected_vertices[7],
+            expected_vertices[4],
+            expected_vertices[5],
+            expected_vertices[5],
+            expected_vertices[1],
+            expected_vertices[3],
+            expected_vertices[5],
+            expected_vertices[3],
+            expected_vertices[7],
+            expected_vertices[1],
+            expected_vertices[0],
+            expected_vertices[2],
+            expected_vertices[1],
+            expected_vertices[2],
+            expected_vertices[3],
+            expected_vertices[5],
+            expected_vertices[4],
+            expected_vertices[0],
+            expected_vertices[5],
+            expected_vertices[0],
+            expected_vertices[1],
+        ];
+
+        assert_eq!(model.vertices.len(), 8);
+        assert_eq!(expected_vertices, triangle_points(&model))
+    }
+
+    #[test]
+    fn test_model() {
+        let model = parse_obj(
+            "
+            o Square
+            v -1.000000 -1.000000 1.000000
+            v  1.000000 -1.000000 1.000000
+            v  1.000000  1.000000 1.000000
+            v -1.000000  1.000000 1.000000
+            #
+            f 1/0/0 2/0/0 3/0/0 4/0/0
+            ",
+            &HashMap::new(),
+        );
+
+        assert_eq!(
+            triangle_points(&model),
+            vec![
+                Vector3::new(-1., -1., 1.),
+                Vector3::new(1., -1., 1.),
+                Vector3::new(1., 1., 1.),
+                Vector3::new(-1., -1., 1.),
+                Vector3::new(1., 1., 1.),
+                Vector3::new(-1., 1., 1.)
+            ]
+        )
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut model = parse_obj(
+            "
+            v 2.000000 2.000000 2.000000
+            v 6.000000 2.000000 2.000000
+            v 2.000000 4.000000 2.000000
+            f 1 2 3
+            ",
+            &HashMap::new(),
+        );
+        model.normalize();
+
+        let sphere = model.bounding_sphere();
+        assert_eq!(sphere.center, Vector3::default());
+        assert!((sphere.radius - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_obj_attributes() {
+        let model = load_obj("models/cube.obj").expect("Failed to read model data");
+
+        // Each face of the cube has its own normal, so corners are not shared between faces
+        assert_eq!(model.vertices.len(), 24);
+        assert_eq!(model.triangles.len(), 12);
+        assert_eq!(model.normals.len(), 24);
+        assert_eq!(model.uvs.len(), 24);
+        assert_eq!(model.faces.len(), 6);
+        assert_eq!(model.polygons().next().unwrap().1, vec![0, 1, 2, 3]);
+        assert_eq!(
+            model.groups,
+            vec![Group {
+                name: String::from("Cube"),
+                triangles: 0..12
+            }]
+        );
+
+        // The first corner is `1/1/1`
+        assert_eq!(model.vertices[0], Vector3::new(1., 1., -1.));
+        assert_eq!(model.normals[0], Vector3::new(0., 1., 0.));
+        assert_eq!(model.uvs[0], Vector2::new(0.625, 0.5));
+    }
+
+    #[test]
+    fn test_obj_round_trip() {
+        let mut model = load_obj("models/cube.obj").expect("Failed to read model data");
+        model.face_colors[3] = Color::new(0x12, 0x34, 0x56, 0x78);
+        model.groups.push(Group {
+            name: String::from("Top"),
+            triangles: 12..12,
+        });
+
+        let mut obj = Vec::new();
+        write_obj(&model, &mut obj, Some("cube.mtl")).expect("Failed to write model data");
+        let written = String::from_utf8(obj).expect("OBJ is not valid UTF-8");
+        let parsed = parse_obj(&written, &HashMap::new());
+
+        assert!(written.starts_with("mtllib cube.mtl\n"));
+        assert!(written.contains("usemtl color_12345678\n"));
+        assert_eq!(parsed.vertices, model.vertices);
+        assert_eq!(parsed.normals, model.normals);
+        assert_eq!(parsed.uvs, model.uvs);
+        assert_eq!(parsed.triangles, model.triangles);
+        assert_eq!(parsed.groups, model.groups[..1]);
+        assert_eq!(parsed.face_colors, model.face_colors);
+
+        // Colors come from the material library first
+        let materials = parse_mtl("newmtl color_12345678\nKd 1 0 0.5\nd 0.5\n");
+        let parsed = parse_obj(&written, &materials);
+        assert_eq!(parsed.face_colors[3], Color::new(0xff, 0, 0x80, 0x80));
+        assert_eq!(parsed.face_colors[4], model.face_colors[4]);
+
+        // The quad with two colors is written as two triangles
+        assert_eq!(parsed.faces.len(), 7);
+        assert_eq!(parsed.faces[..3], [0..2, 2..3, 3..4]);
+    }
+
+    #[test]
+    fn test_write_mtl() {
+        let red = Color::new(0xff, 0, 0, 0xff);
+        let clear = Color::new(0, 0, 0, 0);
+        let model = Model::new(
+            vec![Vector3::default(); 3],
+            vec![[0, 1, 2]; 3],
+            vec![red, clear, red],
+        );
+
+        let mut mtl = Vec::new();
+        write_mtl(&model, &mut mtl).expect("Failed to write materials");
+
+        assert_eq!(
+            String::from_utf8(mtl).expect("MTL is not valid UTF-8"),
+            "newmtl color_ff0000ff\nKd 1 0 0\nd 1\n\nnewmtl color_00000000\nKd 0 0 0\nd 0\n\n"
+        );
+    }
+}
@@ -0,0 +1,522 @@
+use std::{collections::HashMap, f64::consts::PI, ops::Range};
+
+use crate::{
+    screen::Color,
+    vector::{vector2::Vector2, vector3::Vector3},
+};
+
+use super::Model;
+
+/// Builds a sphere out of rings of quads, with `segments` around the y axis and `rings` from
+/// pole to pole. At least 3 segments and 2 rings are used
+pub fn uv_sphere(radius: f64, segments: usize, rings: usize) -> Model {
+    let (segments, rings) = (segments.max(3), rings.max(2));
+    let profile: Vec<ProfilePoint> = (0..=rings)
+        .map(|j| {
+            let (sin, cos) = (PI * j as f64 / rings as f64).sin_cos();
+
+            ProfilePoint {
+                radius: radius * sin,
+                y: radius * cos,
+                normal: Vector2::new(sin, cos),
+                v: j as f64 / rings as f64,
+            }
+        })
+        .collect();
+
+    let mut builder = MeshBuilder::default();
+    builder.lathe(&profile, segments);
+    builder.build()
+}
+
+/// Builds a sphere by splitting each face of an icosahedron in 4, `subdivisions` times
+pub fn icosphere(radius: f64, subdivisions: usize) -> Model {
+    let t = (1. + 5f64.sqrt()) / 2.;
+
+    let mut points: Vec<Vector3<f64>> = [
+        (-1., t, 0.),
+        (1., t, 0.),
+        (-1., -t, 0.),
+        (1., -t, 0.),
+        (0., -1., t),
+        (0., 1., t),
+        (0., -1., -t),
+        (0., 1., -t),
+        (t, 0., -1.),
+        (t, 0., 1.),
+        (-t, 0., -1.),
+        (-t, 0., 1.),
+    ]
+    .into_iter()
+    .map(|(x, y, z)| Vector3::new(x, y, z).normalize())
+    .collect();
+
+    let mut faces: Vec<[usize; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Edges are shared by two faces, which must reuse the same midpoint
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push(((points[a] + points[b]) / 2.).normalize());
+                points.len() - 1
+            })
+        };
+
+        faces = faces
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    // Spherical texture coordinates break on the seam at u = 0 and at the poles, so every face
+    // gets its own corners and those are fixed one face at a time
+    let mut builder = MeshBuilder::default();
+    for face in faces {
+        let mut uvs = face.map(|idx| {
+            let point = points[idx];
+            Vector2::new(
+                0.5 + point.x.atan2(point.z) / (2. * PI),
+                point.y.clamp(-1., 1.).acos() / PI,
+            )
+        });
+
+        // Faces crossing the seam wrap around from u = 1 to u = 0
+        let crosses_seam = uvs.iter().any(|uv| uv.x > 0.75) && uvs.iter().any(|uv| uv.x < 0.25);
+        if crosses_seam {
+            uvs.iter_mut()
+                .filter(|uv| uv.x < 0.5)
+                .for_each(|uv| uv.x += 1.);
+        }
+
+        // Poles have no longitude, use the one in the middle of the face instead
+        for corner in 0..3 {
+            let point = points[face[corner]];
+            if point.x.abs() < 1e-9 && point.z.abs() < 1e-9 {
+                uvs[corner].x = (uvs[(corner + 1) % 3].x + uvs[(corner + 2) % 3].x) / 2.;
+            }
+        }
+
+        let corners = [0, 1, 2].map(|corner| {
+            let point = points[face[corner]];
+            builder.push_vertex(point * radius, point, uvs[corner])
+        });
+        builder.push_face(&corners);
+    }
+
+    let mut model = builder.build();
+    model.deduplicate();
+    model
+}
+
+/// Builds a ring around the y axis. The tube has `minor_segments` around its own center and
+/// `major_segments` around the y axis, at least 3 of each
+pub fn torus(
+    major_radius: f64,
+    minor_radius: f64,
+    major_segments: usize,
+    minor_segments: usize,
+) -> Model {
+    let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+    // Start from the outer edge and go around the tube downwards
+    let profile: Vec<ProfilePoint> = (0..=minor_segments)
+        .map(|j| {
+            let (sin, cos) = (2. * PI * j as f64 / minor_segments as f64).sin_cos();
+
+            ProfilePoint {
+                radius: major_radius + minor_radius * cos,
+                y: -minor_radius * sin,
+                normal: Vector2::new(cos, -sin),
+                v: j as f64 / minor_segments as f64,
+            }
+        })
+        .collect();
+
+    let mut builder = MeshBuilder::default();
+    builder.lathe(&profile, major_segments);
+    builder.build()
+}
+
+/// Builds a closed cylinder along the y axis, centered at the origin, with at least 3 segments
+pub fn cylinder(radius: f64, height: f64, segments: usize) -> Model {
+    let segments = segments.max(3);
+    let profile = [
+        ProfilePoint {
+            radius,
+            y: height / 2.,
+            normal: Vector2::new(1., 0.),
+            v: 0.,
+        },
+        ProfilePoint {
+            radius,
+            y: -height / 2.,
+            normal: Vector2::new(1., 0.),
+            v: 1.,
+        },
+    ];
+
+    let mut builder = MeshBuilder::default();
+    builder.lathe(&profile, segments);
+    builder.disc(radius, height / 2., 1., segments);
+    builder.disc(radius, -height / 2., -1., segments);
+    builder.build()
+}
+
+/// Builds a closed cone along the y axis, with the tip pointing up and centered at the origin,
+/// with at least 3 segments
+pub fn cone(radius: f64, height: f64, segments: usize) -> Model {
+    let segments = segments.max(3);
+    // The side normal is perpendicular to the slope going from the tip to the base
+    let slope = (height * height + radius * radius).sqrt();
+    let normal = Vector2::new(height / slope, radius / slope);
+
+    let profile = [
+        ProfilePoint {
+            radius: 0.,
+            y: height / 2.,
+            normal,
+            v: 0.,
+        },
+        ProfilePoint {
+            radius,
+            y: -height / 2.,
+            normal,
+            v: 1.,
+        },
+    ];
+
+    let mut builder = MeshBuilder::default();
+    builder.lathe(&profile, segments);
+    builder.disc(radius, -height / 2., -1., segments);
+    builder.build()
+}
+
+/// Builds a flat grid on the xz plane facing up, split in `columns` along x and `rows` along z,
+/// at least 1 of each
+pub fn plane(width: f64, depth: f64, columns: usize, rows: usize) -> Model {
+    let (columns, rows) = (columns.max(1), rows.max(1));
+    let mut builder = MeshBuilder::default();
+
+    for j in 0..=rows {
+        for i in 0..=columns {
+            let uv = Vector2::new(i as f64 / columns as f64, j as f64 / rows as f64);
+
+            builder.push_vertex(
+                Vector3::new(width * (uv.x - 0.5), 0., depth * (uv.y - 0.5)),
+                Vector3::new(0., 1., 0.),
+                uv,
+            );
+        }
+    }
+
+    builder.quads(0, columns + 1, rows + 1);
+    builder.build()
+}
+
+/// Builds a cylinder along the y axis closed by two hemispheres, where `height` includes the
+/// hemispheres and `rings` is the number of rings of each hemisphere. At least 3 segments and 1
+/// ring are used
+pub fn capsule(radius: f64, height: f64, segments: usize, rings: usize) -> Model {
+    let (segments, rings) = (segments.max(3), rings.max(1));
+    let body = (height - 2. * radius).max(0.);
+    let length = PI * radius + body;
+
+    let hemisphere_point = |angle: f64, center: f64, arc_start: f64| {
+        let (sin, cos) = angle.sin_cos();
+
+        ProfilePoint {
+            radius: radius * sin,
+            y: center + radius * cos,
+            normal: Vector2::new(sin, cos),
+            v: (arc_start + radius * angle) / length,
+        }
+    };
+
+    // The body is the strip between the two equators
+    let top =
+        (0..=rings).map(|j| hemisphere_point(PI / 2. * j as f64 / rings as f64, body / 2., 0.));
+    let bottom = (0..=rings)
+        .map(|j| hemisphere_point(PI / 2. * (1. + j as f64 / rings as f64), -body / 2., body));
+    let profile: Vec<ProfilePoint> = top.chain(bottom).collect();
+
+    let mut builder = MeshBuilder::default();
+    builder.lathe(&profile, segments);
+    builder.build()
+}
+
+/// Vertex data of a model being generated
+#[derive(Default)]
+struct MeshBuilder {
+    vertices: Vec<Vector3<f64>>,
+    normals: Vec<Vector3<f64>>,
+    uvs: Vec<Vector2<f64>>,
+    triangles: Vec<[usize; 3]>,
+    faces: Vec<Range<usize>>,
+}
+
+/// Point of a profile rotated around the y axis
+struct ProfilePoint {
+    /// Distance from the y axis
+    radius: f64,
+    y: f64,
+    /// Normal on the plane of the profile, as (away from the y axis, y)
+    normal: Vector2<f64>,
+    /// Texture coordinate along the profile
+    v: f64,
+}
+
+impl MeshBuilder {
+    fn push_vertex(
+        &mut self,
+        position: Vector3<f64>,
+        normal: Vector3<f64>,
+        uv: Vector2<f64>,
+    ) -> usize {
+        self.vertices.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+
+        self.vertices.len() - 1
+    }
+
+    /// Adds a polygon split in a fan of triangles around its first corner
+    fn push_face(&mut self, corners: &[usize]) {
+        let first = self.triangles.len();
+        for i in 2..corners.len() {
+            self.push_triangle([corners[0], corners[i - 1], corners[i]]);
+        }
+
+        if self.triangles.len() > first {
+            self.faces.push(first..self.triangles.len());
+        }
+    }
+
+    /// Adds a triangle, unless two of its corners are in the same spot (like at the poles)
+    fn push_triangle(&mut self, triangle: [usize; 3]) {
+        let [a, b, c] = triangle.map(|idx| self.vertices[idx]);
+        let (ab, ac) = (b - a, c - a);
+
+        // Rounding errors keep the corners slightly apart, compare the area to the edges
+        let area = ab.cross(&ac);
+        if area * area > 1e-20 * (ab * ab) * (ac * ac) {
+            self.triangles.push(triangle);
+        }
+    }
+
+    /// Connects a grid of vertices, stored row by row starting from `first`. Rows go from top
+    /// to bottom and columns from left to right, as seen from the side the normals point to
+    fn quads(&mut self, first: usize, columns: usize, rows: usize) {
+        for j in 0..rows - 1 {
+            for i in 0..columns - 1 {
+                let top_left = first + j * columns + i;
+                let bottom_left = top_left + columns;
+
+                self.push_face(&[top_left, bottom_left, bottom_left + 1, top_left + 1]);
+            }
+        }
+    }
+
+    /// Rotates a profile around the y axis. The profile goes from top to bottom as seen from
+    /// outside, so that the faces wind counter clockwise when looking at them from the outside
+    fn lathe(&mut self, profile: &[ProfilePoint], segments: usize) {
+        let first = self.vertices.len();
+
+        for point in profile {
+            // The seam is repeated to have both u = 0 and u = 1
+            for i in 0..=segments {
+                let u = i as f64 / segments as f64;
+                let (sin, cos) = (2. * PI * u).sin_cos();
+
+                self.push_vertex(
+                    Vector3::new(point.radius * sin, point.y, point.radius * cos),
+                    Vector3::new(point.normal.x * sin, point.normal.y, point.normal.x * cos),
+                    Vector2::new(u, point.v),
+                );
+            }
+        }
+
+        self.quads(first, segments + 1, profile.len());
+    }
+
+    /// Adds a disc perpendicular to the y axis, facing up if `facing` is positive or down
+    /// otherwise. The texture is projected from the side the disc faces
+    fn disc(&mut self, radius: f64, y: f64, facing: f64, segments: usize) {
+        let normal = Vector3::new(0., facing.signum(), 0.);
+        let center = self.push_vertex(Vector3::new(0., y, 0.), normal, Vector2::new(0.5, 0.5));
+
+        for i in 0..=segments {
+            let (sin, cos) = (2. * PI * i as f64 / segments as f64).sin_cos();
+
+            self.push_vertex(
+                Vector3::new(radius * sin, y, radius * cos),
+                normal,
+                Vector2::new(0.5 + sin / 2., 0.5 + normal.y * cos / 2.),
+            );
+        }
+
+        for i in 0..segments {
+            let (current, next) = (center + 1 + i, center + 2 + i);
+
+            if facing > 0. {
+                self.push_face(&[center, current, next]);
+            } else {
+                self.push_face(&[center, next, current]);
+            }
+        }
+    }
+
+    fn build(self) -> Model {
+        let face_colors = vec![Color::new(0xff, 0xff, 0xff, 0xff); self.triangles.len()];
+
+        let mut model = Model::new(self.vertices, self.triangles, face_colors);
+        model.faces = self.faces;
+        model.normals = self.normals;
+        model.uvs = self.uvs;
+        model
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Checks that normals have unit length, texture coordinates are in range and that every
+    /// triangle winds counter clockwise around the normals of its corners
+    fn assert_valid(model: &Model) {
+        assert!(!model.triangles.is_empty());
+        assert_eq!(model.normals.len(), model.vertices.len());
+        assert_eq!(model.uvs.len(), model.vertices.len());
+        assert_eq!(model.face_colors.len(), model.triangles.len());
+
+        for normal in &model.normals {
+            assert!(((*normal * *normal) - 1.).abs() < 1e-9, "{normal:?}");
+        }
+
+        // Faces wrapping around the seam of the icosphere go past u = 1
+        for uv in &model.uvs {
+            assert!(
+                (0. ..=1.25).contains(&uv.x) && (0. ..=1.).contains(&uv.y),
+                "{uv:?}"
+            );
+        }
+
+        for &[a, b, c] in &model.triangles {
+            let face_normal = (model.vertices[b] - model.vertices[a])
+                .cross(&(model.vertices[c] - model.vertices[a]));
+            let vertex_normals = model.normals[a] + model.normals[b] + model.normals[c];
+
+            assert!(face_normal * vertex_normals > 0., "{:?}", [a, b, c]);
+        }
+    }
+
+    #[test]
+    fn test_uv_sphere() {
+        let sphere = uv_sphere(2., 16, 8);
+        assert_valid(&sphere);
+
+        assert_eq!(sphere.vertices.len(), 17 * 9);
+        assert_eq!(sphere.triangles.len(), 16 * (2 * 8 - 2));
+        for vertex in &sphere.vertices {
+            assert!((vertex.length() - 2.).abs() < 1e-9);
+        }
+
+        // Too few segments or rings are raised to the minimum
+        assert_valid(&uv_sphere(1., 0, 0));
+        assert_eq!(uv_sphere(1., 0, 0).vertices, uv_sphere(1., 3, 2).vertices);
+    }
+
+    #[test]
+    fn test_icosphere() {
+        let sphere = icosphere(1.5, 2);
+        assert_valid(&sphere);
+
+        assert_eq!(sphere.triangles.len(), 20 * 4 * 4);
+        for vertex in &sphere.vertices {
+            assert!((vertex.length() - 1.5).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_torus() {
+        let torus = torus(2., 0.5, 24, 12);
+        assert_valid(&torus);
+
+        assert_eq!(torus.triangles.len(), 2 * 24 * 12);
+
+        assert_valid(&super::torus(2., 0.5, 0, 0));
+        assert_eq!(
+            super::torus(2., 0.5, 0, 0).vertices,
+            super::torus(2., 0.5, 3, 3).vertices
+        );
+    }
+
+    #[test]
+    fn test_cylinder_and_cone() {
+        assert_valid(&cylinder(1., 2., 12));
+        assert_valid(&cone(1., 2., 12));
+
+        assert_eq!(cylinder(1., 2., 12).triangles.len(), 12 * 4);
+        assert_eq!(cone(1., 2., 12).triangles.len(), 12 * 2);
+
+        assert_valid(&cylinder(1., 2., 0));
+        assert_valid(&cone(1., 2., 0));
+        assert_eq!(cylinder(1., 2., 0).vertices, cylinder(1., 2., 3).vertices);
+        assert_eq!(cone(1., 2., 0).vertices, cone(1., 2., 3).vertices);
+    }
+
+    #[test]
+    fn test_plane() {
+        let plane = plane(4., 2., 4, 3);
+        assert_valid(&plane);
+
+        assert_eq!(plane.vertices.len(), 5 * 4);
+        assert_eq!(plane.triangles.len(), 2 * 4 * 3);
+        assert_eq!(plane.aabb().size(), Vector3::new(4., 0., 2.));
+
+        assert_valid(&super::plane(4., 2., 0, 0));
+        assert_eq!(
+            super::plane(4., 2., 0, 0).vertices,
+            super::plane(4., 2., 1, 1).vertices
+        );
+    }
+
+    #[test]
+    fn test_capsule() {
+        let capsule = capsule(0.5, 3., 16, 4);
+        assert_valid(&capsule);
+
+        let size = capsule.aabb().size();
+        assert!((size.y - 3.).abs() < 1e-9);
+        assert!((size.x - 1.).abs() < 1e-9);
+
+        assert_valid(&super::capsule(0.5, 3., 0, 0));
+        assert_eq!(
+            super::capsule(0.5, 3., 0, 0).vertices,
+            super::capsule(0.5, 3., 3, 1).vertices
+        );
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    ops::{Add, AddAssign},
+};
+
+use crate::vector::vector3::Vector3;
+
+use super::{Group, Model};
+
+/// How much more moving a vertex off an open edge costs than moving it off a face
+const BOUNDARY_WEIGHT: f64 = 1000.;
+
+impl Model {
+    /// Reduces the model to at most `target_triangles` triangles, collapsing first the edges that
+    /// change its shape the least (quadric error metrics, Garland and Heckbert).
+    ///
+    /// Vertices in the same position are collapsed together, so texture and normal seams don't
+    /// open. The model may keep more triangles if collapsing them would flip some faces
+    pub fn simplify(&self, target_triangles: usize) -> Model {
+        let mut simplifier = Simplifier::new(self);
+        simplifier.run(target_triangles);
+        simplifier.into_model(self)
+    }
+
+    /// Adds up to `levels` levels of detail, each with half of the triangles of the previous one
+    pub fn with_lods(mut self, levels: usize) -> Self {
+        let mut lods: Vec<Model> = Vec::new();
+
+        for _ in 0..levels {
+            let previous = lods.last().unwrap_or(&self);
+            let lod = previous.simplify(previous.triangles.len() / 2);
+
+            // Stop when the model can't be simplified any further
+            if lod.triangles.is_empty() || lod.triangles.len() == previous.triangles.len() {
+                break;
+            }
+            lods.push(lod);
+        }
+
+        self.lods = lods;
+        self
+    }
+}
+
+/// Symmetric 4x4 matrix measuring the squared distance of a point from a set of planes
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Quadric of the plane `normal * p + offset = 0`, where `normal` has length 1
+    fn from_plane(normal: Vector3<f64>, offset: f64, weight: f64) -> Self {
+        let [a, b, c, d] = [normal.x, normal.y, normal.z, offset];
+
+        Quadric(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|coefficient| coefficient * weight),
+        )
+    }
+
+    fn error(&self, point: Vector3<f64>) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let Vector3 { x, y, z } = point;
+
+        aa * x * x
+            + bb * y * y
+            + cc * z * z
+            + 2. * (ab * x * y + ac * x * z + bc * y * z)
+            + 2. * (ad * x + bd * y + cd * z)
+            + dd
+    }
+
+    /// Point with the smallest error, if there is only one
+    fn minimum(&self) -> Option<Vector3<f64>> {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, _] = self.0;
+
+        // Solve the linear system with Cramer's rule
+        let det3 = |c0: [f64; 3], c1: [f64; 3], c2: [f64; 3]| {
+            c0[0] * (c1[1] * c2[2] - c2[1] * c1[2]) - c1[0] * (c0[1] * c2[2] - c2[1] * c0[2])
+                + c2[0] * (c0[1] * c1[2] - c1[1] * c0[2])
+        };
+        let columns = [[aa, ab, ac], [ab, bb, bc], [ac, bc, cc]];
+        let rhs = [-ad, -bd, -cd];
+
+        let det = det3(columns[0], columns[1], columns[2]);
+        let scale = self.0.iter().map(|c| c.abs()).fold(0., f64::max);
+        if det.abs() <= 1e-12 * scale.powi(3) {
+            return None;
+        }
+
+        Some(Vector3::new(
+            det3(rhs, columns[1], columns[2]) / det,
+            det3(columns[0], rhs, columns[2]) / det,
+            det3(columns[0], columns[1], rhs) / det,
+        ))
+    }
+}
+
+impl Add for Quadric {
+    type Output = Quadric;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for Quadric {
+    fn add_assign(&mut self, rhs: Self) {
+        for (a, b) in self.0.iter_mut().zip(rhs.0) {
+            *a += b;
+        }
+    }
+}
+
+/// Edge that can be collapsed into a single point
+#[derive(Debug)]
+struct Collapse {
+    cost: f64,
+    target: Vector3<f64>,
+    edge: (usize, usize),
+    /// Versions of the endpoints when the cost was computed
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    /// Cheaper collapses come first in the heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.edge.cmp(&self.edge))
+    }
+}
+
+/// Simplification state. The mesh topology is tracked on points, vertices of the model welded
+/// by position
+struct Simplifier {
+    /// Point of each vertex of the model
+    points_of: Vec<usize>,
+    positions: Vec<Vector3<f64>>,
+    quadrics: Vec<Quadric>,
+    /// Point each one was collapsed into, itself if it still exists
+    merged_into: Vec<usize>,
+    /// Incremented on every change, to skip the outdated collapses left in the heap
+    versions: Vec<u32>,
+    /// Triangles touching each point, including some removed ones
+    adjacency: Vec<Vec<usize>>,
+
+    /// Points at the corners of each triangle
+    triangles: Vec<[usize; 3]>,
+    removed: Vec<bool>,
+    triangle_count: usize,
+
+    heap: BinaryHeap<Collapse>,
+}
+
+impl Simplifier {
+    fn new(model: &Model) -> Self {
+        let (points_of, positions) = model.weld_positions();
+
+        let triangles: Vec<[usize; 3]> = model
+            .triangles
+            .iter()
+            .map(|triangle| triangle.map(|idx| points_of[idx]))
+            .collect();
+        let removed: Vec<bool> = triangles.iter().map(|&t| is_degenerate(t)).collect();
+
+        let mut simplifier = Simplifier {
+            points_of,
+            quadrics: vec![Quadric::default(); positions.len()],
+            merged_into: (0..positions.len()).collect(),
+            versions: vec![0; positions.len()],
+            adjacency: vec![Vec::new(); positions.len()],
+            positions,
+            triangle_count: removed.iter().filter(|&&removed| !removed).count(),
+            triangles,
+            removed,
+            heap: BinaryHeap::new(),
+        };
+        simplifier.init_quadrics();
+
+        let mut edges = HashSet::new();
+        for (idx, &[a, b, c]) in simplifier.triangles.iter().enumerate() {
+            if simplifier.removed[idx] {
+                continue;
+            }
+
+            for point in [a, b, c] {
+                simplifier.adjacency[point].push(idx);
+            }
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                edges.insert((from.min(to), from.max(to)));
+            }
+        }
+
+        for (a, b) in edges {
+            simplifier.push_collapse(a, b);
+        }
+
+        simplifier
+    }
+
+    /// Sums the planes of the faces around each point, and planes perpendicular to the open
+    /// edges so that the outline of the model is kept
+    fn init_quadrics(&mut self) {
+        let mut edge_faces: HashMap<(usize, usize), (usize, Vector3<f64>)> = HashMap::new();
+
+        for (idx, &[a, b, c]) in self.triangles.iter().enumerate() {
+            if self.removed[idx] {
+                continue;
+            }
+
+            let [pa, pb, pc] = [a, b, c].map(|point| self.positions[point]);
+            let normal = (pb - pa).cross(&(pc - pa));
+            let double_area = normal.length();
+            if double_area == 0. {
+                continue;
+            }
+
+            let normal = normal / double_area;
+            let quadric = Quadric::from_plane(normal, -(normal * pa), double_area / 2.);
+            for point in [a, b, c] {
+                self.quadrics[point] += quadric;
+            }
+
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                edge_faces
+                    .entry((from.min(to), from.max(to)))
+                    .and_modify(|(count, _)| *count += 1)
+                    .or_insert((1, normal));
+            }
+        }
+
+        for ((a, b), (count, normal)) in edge_faces {
+            if count != 1 {
+                continue;
+            }
+
+            let edge = self.positions[b] - self.positions[a];
+            let length_squared = edge * edge;
+            let perpendicular = edge.cross(&normal).normalize();
+            if perpendicular == Vector3::default() {
+                continue;
+            }
+
+            let quadric = Quadric::from_plane(
+                perpendicular,
+                -(perpendicular * self.positions[a]),
+                BOUNDARY_WEIGHT * length_squared,
+            );
+            self.quadrics[a] += quadric;
+            self.quadrics[b] += quadric;
+        }
+    }
+
+    /// Queues the collapse of the edge between two points
+    fn push_collapse(&mut self, a: usize, b: usize) {
+        let quadric = self.quadrics[a] + self.quadrics[b];
+
+        // Fall back to the best among the endpoints and the midpoint if there is no single best
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        let target = quadric
+            .minimum()
+            .filter(|target| {
+                let extent = (pb - pa) * (pb - pa);
+                (*target - pa) * (*target - pa) <= 4. * extent.max(f64::MIN_POSITIVE)
+            })
+            .unwrap_or_else(|| {
+                [pa, pb, (pa + pb) / 2.]
+                    .into_iter()
+                    .min_by(|&p, &q| quadric.error(p).total_cmp(&quadric.error(q)))
+                    .unwrap()
+            });
+
+        self.heap.push(Collapse {
+            cost: quadric.error(target).max(0.),
+            target,
+            edge: (a, b),
+            versions: (self.versions[a], self.versions[b]),
+        });
+    }
+
+    fn run(&mut self, target_triangles: usize) {
+        while self.triangle_count > target_triangles {
+            let Some(collapse) = self.heap.pop() else {
+                break;
+            };
+
+            let (a, b) = collapse.edge;
+            let outdated = self.merged_into[a] != a
+                || self.merged_into[b] != b
+                || collapse.versions != (self.versions[a], self.versions[b]);
+
+            if outdated || self.flips_faces(a, b, collapse.target) {
+                continue;
+            }
+
+            self.collapse(a, b, collapse.target);
+        }
+    }
+
+    /// Checks if moving both points to `target` turns any of the remaining faces around
+    fn flips_faces(&self, a: usize, b: usize, target: Vector3<f64>) -> bool {
+        [a, b].iter().any(|&moved| {
+            self.adjacency[moved].iter().any(|&idx| {
+                let triangle = self.triangles[idx];
+                if self.removed[idx] || (triangle.contains(&a) && triangle.contains(&b)) {
+                    return false;
+                }
+
+                let before = triangle.map(|point| self.positions[point]);
+                let after = triangle.map(|point| match point == moved {
+                    true => target,
+                    false => self.positions[point],
+                });
+
+                let normal = |[p0, p1, p2]: [Vector3<f64>; 3]| (p1 - p0).cross(&(p2 - p0));
+                normal(before) * normal(after) <= 0.
+            })
+        })
+    }
+
+    /// Merges `b` into `a`, placing it at `target`
+    fn collapse(&mut self, a: usize, b: usize, target: Vector3<f64>) {
+        self.positions[a] = target;
+        self.quadrics[a] = self.quadrics[a] + self.quadrics[b];
+        self.merged_into[b] = a;
+        self.versions[a] += 1;
+        self.versions[b] += 1;
+
+        for idx in std::mem::take(&mut self.adjacency[b]) {
+            if self.removed[idx] {
+                continue;
+            }
+
+            let triangle = self.triangles[idx].map(|point| if point == b { a } else { point });
+            self.triangles[idx] = triangle;
+
+            if is_degenerate(triangle) {
+                self.removed[idx] = true;
+                self.triangle_count -= 1;
+            } else {
+                self.adjacency[a].push(idx);
+            }
+        }
+
+        let removed = &self.removed;
+        self.adjacency[a].retain(|&idx| !removed[idx]);
+
+        // The cost of collapsing every edge around the moved point changed
+        let neighbors: HashSet<usize> = self.adjacency[a]
+            .iter()
+            .flat_map(|&idx| self.triangles[idx])
+            .filter(|&point| point != a)
+            .collect();
+
+        for neighbor in neighbors {
+            self.push_collapse(a.min(neighbor), a.max(neighbor));
+        }
+    }
+
+    /// Moves the vertices of the model to the simplified positions and keeps only the remaining
+    /// triangles
+    fn into_model(self, model: &Model) -> Model {
+        let point_position = |mut point: usize| {
+            while self.merged_into[point] != point {
+                point = self.merged_into[point];
+            }
+            self.positions[point]
+        };
+
+        let kept: Vec<usize> = (0..model.triangles.len())
+            .filter(|&idx| !self.removed[idx])
+            .collect();
+
+        // Only copy the vertices that are still used
+        let mut remap = vec![None; model.vertices.len()];
+        let mut used = Vec::new();
+        let triangles = kept
+            .iter()
+            .map(|&idx| {
+                model.triangles[idx].map(|vertex| {
+                    *remap[vertex].get_or_insert_with(|| {
+                        used.push(vertex);
+                        used.len() - 1
+                    })
+                })
+            })
+            .collect();
+
+        let mut simplified = Model::new(
+            used.iter()
+                .map(|&vertex| point_position(self.points_of[vertex]))
+                .collect(),
+            triangles,
+            kept.iter().map(|&idx| model.face_colors[idx]).collect(),
+        );
+
+        if !model.normals.is_empty() {
+            simplified.normals = used.iter().map(|&vertex| model.normals[vertex]).collect();
+        }
+        if !model.uvs.is_empty() {
+            simplified.uvs = used.iter().map(|&vertex| model.uvs[vertex]).collect();
+        }
+        simplified.texture = model.texture.clone();
+
+        // Groups shrink to the triangles they kept
+        let kept_before = |end: usize| kept.partition_point(|&idx| idx < end);
+        simplified.groups = model
+            .groups
+            .iter()
+            .map(|group| Group {
+                name: group.name.clone(),
+                triangles: kept_before(group.triangles.start)..kept_before(group.triangles.end),
+            })
+            .filter(|group| !group.triangles.is_empty())
+            .collect();
+
+        // Collapsed vertices on both sides of a seam may now be identical
+        simplified.deduplicate();
+
+        simplified
+    }
+}
+
+fn is_degenerate([a, b, c]: [usize; 3]) -> bool {
+    a == b || b == c || c == a
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        model::primitives::{plane, uv_sphere},
+        vector::Vector,
+    };
+
+    #[test]
+    fn test_simplify_plane() {
+        let model = plane(2., 2., 8, 8);
+        let simplified = model.simplify(8);
+
+        assert!(simplified.triangles.len() <= 8);
+        assert!(!simplified.triangles.is_empty());
+
+        // A flat model stays flat, and its outline is preserved
+        assert!(
+            simplified
+                .vertices
+                .iter()
+                .all(|vertex| vertex.y.abs() < 1e-9)
+        );
+        let (aabb, simplified_aabb) = (model.aabb(), simplified.aabb());
+        assert!(
+            (aabb.size() - simplified_aabb.size())
+                .components()
+                .iter()
+                .all(|c| c.abs() < 1e-6)
+        );
+    }
+
+    #[test]
+    fn test_simplify_sphere() {
+        let model = uv_sphere(1., 32, 16);
+        let simplified = model.simplify(100);
+
+        assert!(simplified.triangles.len() <= 100);
+        assert!(simplified.triangles.len() >= 90);
+        assert_eq!(simplified.face_colors.len(), simplified.triangles.len());
+        assert_eq!(simplified.uvs.len(), simplified.vertices.len());
+
+        // Every vertex stays close to the surface
+        for vertex in &simplified.vertices {
+            let distance = vertex.length();
+            assert!((distance - 1.).abs() < 0.15, "{distance}");
+        }
+    }
+
+    #[test]
+    fn test_with_lods() {
+        let model = uv_sphere(1., 32, 16).with_lods(3);
+
+        assert_eq!(model.lods.len(), 3);
+
+        let mut triangles = model.triangles.len();
+        for lod in &model.lods {
+            assert!(lod.triangles.len() <= triangles / 2);
+            triangles = lod.triangles.len();
+        }
+    }
+}
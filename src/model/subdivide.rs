@@ -0,0 +1,499 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::vector::{vector2::Vector2, vector3::Vector3};
+
+use super::{Group, Model};
+
+/// Rule used to split and smooth the faces of a model
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Subdivision {
+    /// Splits each triangle in 4 (Loop). Polygons are subdivided as the triangles they are
+    /// made of
+    Loop,
+    /// Splits each polygon with n corners in n quads (Catmull-Clark)
+    CatmullClark,
+}
+
+impl Model {
+    /// Smooths the model by subdividing its faces `levels` times.
+    ///
+    /// Vertices in the same position are moved together, so texture and normal seams don't
+    /// open. Open edges are smoothed along the outline of the model, and texture coordinates
+    /// are interpolated linearly inside each face
+    pub fn subdivide(&self, scheme: Subdivision, levels: usize) -> Model {
+        let mut model = self.clone();
+
+        for _ in 0..levels {
+            model = subdivide_once(&model, scheme);
+        }
+
+        model
+    }
+}
+
+/// Faces of a model, connected through the positions of their corners
+struct Topology {
+    /// Point of each vertex of the model
+    points_of: Vec<usize>,
+    positions: Vec<Vector3<f64>>,
+    /// Points at the corners of each face
+    faces: Vec<Vec<usize>>,
+    /// Faces on the sides of each edge, keyed by its points in increasing order
+    edge_faces: HashMap<(usize, usize), Vec<usize>>,
+    /// Points sharing an edge with each point
+    neighbors: Vec<Vec<usize>>,
+    /// Faces each point is a corner of
+    point_faces: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    fn new(model: &Model, faces: &[Vec<usize>]) -> Self {
+        let (points_of, positions) = model.weld_positions();
+
+        let mut topology = Topology {
+            faces: faces
+                .iter()
+                .map(|corners| corners.iter().map(|&vertex| points_of[vertex]).collect())
+                .collect(),
+            edge_faces: HashMap::new(),
+            neighbors: vec![Vec::new(); positions.len()],
+            point_faces: vec![Vec::new(); positions.len()],
+            points_of,
+            positions,
+        };
+
+        for (face_idx, face) in topology.faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                let b = face[(i + 1) % face.len()];
+                topology.point_faces[a].push(face_idx);
+
+                let faces = topology.edge_faces.entry(edge(a, b)).or_default();
+                if faces.is_empty() {
+                    topology.neighbors[a].push(b);
+                    topology.neighbors[b].push(a);
+                }
+                faces.push(face_idx);
+            }
+        }
+
+        topology
+    }
+
+    /// Edges with a single face are on the outline of the model. Edges shared by more than two
+    /// faces are treated the same way
+    fn is_boundary(&self, a: usize, b: usize) -> bool {
+        self.edge_faces[&edge(a, b)].len() != 2
+    }
+
+    /// New position of a point on the outline, which only depends on the outline itself.
+    /// `None` for inner points
+    fn boundary_position(&self, point: usize) -> Option<Vector3<f64>> {
+        let boundary: Vec<usize> = self.neighbors[point]
+            .iter()
+            .copied()
+            .filter(|&neighbor| self.is_boundary(point, neighbor))
+            .collect();
+
+        match boundary.as_slice() {
+            [] => None,
+            &[a, b] => {
+                Some(self.positions[point] * 0.75 + (self.positions[a] + self.positions[b]) * 0.125)
+            }
+            // Corners of the outline stay in place
+            _ => Some(self.positions[point]),
+        }
+    }
+
+    fn midpoint(&self, a: usize, b: usize) -> Vector3<f64> {
+        (self.positions[a] + self.positions[b]) / 2.
+    }
+}
+
+/// Positions of the points of a subdivided model
+struct NewPoints {
+    /// Moved position of each point of the source model
+    vertices: Vec<Vector3<f64>>,
+    /// Point added on each edge
+    edges: HashMap<(usize, usize), Vector3<f64>>,
+    /// Point added in the middle of each face, empty for Loop subdivision
+    faces: Vec<Vector3<f64>>,
+}
+
+fn subdivide_once(model: &Model, scheme: Subdivision) -> Model {
+    let polygons: Vec<(Range<usize>, Vec<usize>)> = match scheme {
+        Subdivision::Loop => model
+            .triangles
+            .iter()
+            .enumerate()
+            .map(|(idx, triangle)| (idx..idx + 1, triangle.to_vec()))
+            .collect(),
+        Subdivision::CatmullClark => model.polygons().collect(),
+    };
+    let corners: Vec<Vec<usize>> = polygons
+        .iter()
+        .map(|(_, corners)| corners.clone())
+        .collect();
+    let topology = Topology::new(model, &corners);
+
+    let mut output = Output {
+        source: model,
+        model: Model::new(Vec::new(), Vec::new(), Vec::new()),
+        triangle_starts: vec![usize::MAX; model.triangles.len() + 1],
+    };
+
+    match scheme {
+        Subdivision::Loop => {
+            let NewPoints {
+                vertices: vertex_points,
+                edges: edge_points,
+                ..
+            } = loop_points(&topology);
+
+            for (face, corners) in &polygons {
+                let &[a, b, c] = corners.as_slice() else {
+                    unreachable!("Loop subdivision only splits triangles");
+                };
+                let [pa, pb, pc] = [a, b, c].map(|vertex| topology.points_of[vertex]);
+
+                let mut corner = |vertex: usize, point: usize| {
+                    output.push_vertex(vertex_points[point], output.source_uv(&[vertex]))
+                };
+                let [va, vb, vc] = [corner(a, pa), corner(b, pb), corner(c, pc)];
+
+                let mut edge_corner = |from: usize, to: usize| {
+                    let point =
+                        edge_points[&edge(topology.points_of[from], topology.points_of[to])];
+                    output.push_vertex(point, output.source_uv(&[from, to]))
+                };
+                let [ab, bc, ca] = [edge_corner(a, b), edge_corner(b, c), edge_corner(c, a)];
+
+                output.push_face(
+                    face.clone(),
+                    &[[va, ab, ca], [ab, vb, bc], [ca, bc, vc], [ab, bc, ca]],
+                    false,
+                );
+            }
+        }
+
+        Subdivision::CatmullClark => {
+            let NewPoints {
+                vertices: vertex_points,
+                edges: edge_points,
+                faces: face_points,
+            } = catmull_clark_points(&topology);
+
+            for (face_idx, (face, corners)) in polygons.iter().enumerate() {
+                let center = output.push_vertex(face_points[face_idx], output.source_uv(corners));
+
+                let n = corners.len();
+                let mut quads = Vec::with_capacity(2 * n);
+                for i in 0..n {
+                    let (previous, current, next) =
+                        (corners[(i + n - 1) % n], corners[i], corners[(i + 1) % n]);
+                    let [point, previous_point, next_point] =
+                        [current, previous, next].map(|vertex| topology.points_of[vertex]);
+
+                    let corner =
+                        output.push_vertex(vertex_points[point], output.source_uv(&[current]));
+                    let next_edge = output.push_vertex(
+                        edge_points[&edge(point, next_point)],
+                        output.source_uv(&[current, next]),
+                    );
+                    let previous_edge = output.push_vertex(
+                        edge_points[&edge(previous_point, point)],
+                        output.source_uv(&[previous, current]),
+                    );
+
+                    quads.push([corner, next_edge, center, previous_edge]);
+                }
+
+                for quad in quads {
+                    output.push_face(
+                        face.clone(),
+                        &[[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]],
+                        true,
+                    );
+                }
+            }
+        }
+    }
+
+    output.build()
+}
+
+/// New positions of the points and of the midpoints of the edges, following the Loop rules
+fn loop_points(topology: &Topology) -> NewPoints {
+    let vertex_points = (0..topology.positions.len())
+        .map(|point| {
+            let neighbors = &topology.neighbors[point];
+            if neighbors.is_empty() {
+                return topology.positions[point];
+            }
+
+            topology.boundary_position(point).unwrap_or_else(|| {
+                let n = neighbors.len() as f64;
+                let beta = if neighbors.len() == 3 {
+                    3. / 16.
+                } else {
+                    3. / (8. * n)
+                };
+                let sum = neighbors.iter().fold(Vector3::default(), |sum, &neighbor| {
+                    sum + topology.positions[neighbor]
+                });
+
+                topology.positions[point] * (1. - n * beta) + sum * beta
+            })
+        })
+        .collect();
+
+    let edge_points = topology
+        .edge_faces
+        .iter()
+        .map(|(&(a, b), faces)| {
+            let position = match faces.as_slice() {
+                &[left, right] => {
+                    // Corners of the two triangles opposite to the edge
+                    let opposite = |face: usize| {
+                        topology.faces[face]
+                            .iter()
+                            .find(|&&point| point != a && point != b)
+                            .map_or(topology.midpoint(a, b), |&point| topology.positions[point])
+                    };
+
+                    (topology.positions[a] + topology.positions[b]) * 0.375
+                        + (opposite(left) + opposite(right)) * 0.125
+                }
+                _ => topology.midpoint(a, b),
+            };
+
+            ((a, b), position)
+        })
+        .collect();
+
+    NewPoints {
+        vertices: vertex_points,
+        edges: edge_points,
+        faces: Vec::new(),
+    }
+}
+
+/// New positions of the points, of the midpoints of the edges and of the centers of the faces,
+/// following the Catmull-Clark rules
+fn catmull_clark_points(topology: &Topology) -> NewPoints {
+    let face_points: Vec<Vector3<f64>> = topology
+        .faces
+        .iter()
+        .map(|face| average(face.iter().map(|&point| topology.positions[point])))
+        .collect();
+
+    let edge_points = topology
+        .edge_faces
+        .iter()
+        .map(|(&(a, b), faces)| {
+            let position = match faces.as_slice() {
+                &[left, right] => {
+                    (topology.positions[a]
+                        + topology.positions[b]
+                        + face_points[left]
+                        + face_points[right])
+                        / 4.
+                }
+                _ => topology.midpoint(a, b),
+            };
+
+            ((a, b), position)
+        })
+        .collect();
+
+    let vertex_points = (0..topology.positions.len())
+        .map(|point| {
+            let neighbors = &topology.neighbors[point];
+            if neighbors.is_empty() {
+                return topology.positions[point];
+            }
+
+            topology.boundary_position(point).unwrap_or_else(|| {
+                let n = neighbors.len() as f64;
+                let faces = average(topology.point_faces[point].iter().map(|&f| face_points[f]));
+                let edges = average(
+                    neighbors
+                        .iter()
+                        .map(|&neighbor| topology.midpoint(point, neighbor)),
+                );
+
+                (faces + edges * 2. + topology.positions[point] * (n - 3.)) / n
+            })
+        })
+        .collect();
+
+    NewPoints {
+        vertices: vertex_points,
+        edges: edge_points,
+        faces: face_points,
+    }
+}
+
+/// Subdivided model being built
+struct Output<'a> {
+    source: &'a Model,
+    model: Model,
+    /// Index of the first subdivided triangle of each triangle of the source, to move the groups
+    triangle_starts: Vec<usize>,
+}
+
+impl Output<'_> {
+    /// Average texture coordinates of some vertices of the source model
+    fn source_uv(&self, vertices: &[usize]) -> Option<Vector2<f64>> {
+        if self.source.uvs.is_empty() {
+            return None;
+        }
+
+        let sum = vertices.iter().fold(Vector2::default(), |sum, &vertex| {
+            sum + self.source.uvs[vertex]
+        });
+        Some(sum / vertices.len() as f64)
+    }
+
+    /// Adds a vertex, duplicates are merged at the end
+    fn push_vertex(&mut self, position: Vector3<f64>, uv: Option<Vector2<f64>>) -> usize {
+        self.model.vertices.push(position);
+        if let Some(uv) = uv {
+            self.model.uvs.push(uv);
+        }
+
+        self.model.vertices.len() - 1
+    }
+
+    /// Adds the triangles that a face of the source was split into, with the same color. If
+    /// `polygon` is set they are kept together as a single face
+    fn push_face(&mut self, source: Range<usize>, triangles: &[[usize; 3]], polygon: bool) {
+        let first = self.model.triangles.len();
+        let color = self.source.face_colors[source.start];
+
+        self.model.triangles.extend_from_slice(triangles);
+        self.model
+            .face_colors
+            .extend(triangles.iter().map(|_| color));
+        if polygon {
+            self.model.faces.push(first..self.model.triangles.len());
+        }
+
+        // Faces of the source are split in order, so a face ends where the next one starts
+        for idx in source.clone() {
+            self.triangle_starts[idx] = self.triangle_starts[idx].min(first);
+        }
+        self.triangle_starts[source.end] = self.model.triangles.len();
+    }
+
+    fn build(mut self) -> Model {
+        let starts = &self.triangle_starts;
+        self.model.groups = self
+            .source
+            .groups
+            .iter()
+            .map(|group| Group {
+                name: group.name.clone(),
+                triangles: starts[group.triangles.start]..starts[group.triangles.end],
+            })
+            .collect();
+
+        if !self.source.normals.is_empty() {
+            self.model.normals = smooth_normals(&self.model);
+        }
+
+        self.model.texture = self.source.texture.clone();
+        self.model.deduplicate();
+        self.model
+    }
+}
+
+/// Normal of each vertex, averaging the faces around its position weighted by their area
+fn smooth_normals(model: &Model) -> Vec<Vector3<f64>> {
+    let (points_of, positions) = model.weld_positions();
+    let mut normals = vec![Vector3::default(); positions.len()];
+
+    for triangle in &model.triangles {
+        let [a, b, c] = triangle.map(|vertex| points_of[vertex]);
+        let normal = (positions[b] - positions[a]).cross(&(positions[c] - positions[a]));
+
+        for point in [a, b, c] {
+            normals[point] += normal;
+        }
+    }
+
+    points_of
+        .iter()
+        .map(|&point| normals[point].normalize())
+        .collect()
+}
+
+fn average(points: impl Iterator<Item = Vector3<f64>>) -> Vector3<f64> {
+    let (sum, count) = points.fold((Vector3::default(), 0), |(sum, count), point| {
+        (sum + point, count + 1)
+    });
+
+    sum / count.max(1) as f64
+}
+
+/// Key of the edge between two points, the same in both directions
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        obj::load_obj,
+        primitives::{icosphere, plane},
+    };
+
+    #[test]
+    fn test_loop_subdivision() {
+        let model = icosphere(1., 0).subdivide(Subdivision::Loop, 2);
+
+        assert_eq!(model.triangles.len(), 20 * 4 * 4);
+        assert_eq!(model.face_colors.len(), model.triangles.len());
+        assert_eq!(model.normals.len(), model.vertices.len());
+
+        // The shape shrinks inside the original one, getting closer to a sphere
+        for vertex in &model.vertices {
+            let distance = vertex.length();
+            assert!(distance > 0.7 && distance < 1., "{distance}");
+        }
+    }
+
+    #[test]
+    fn test_loop_subdivision_boundary() {
+        let model = plane(2., 2., 2, 2);
+        let subdivided = model.subdivide(Subdivision::Loop, 1);
+
+        // A flat model stays flat and keeps its corners
+        assert_eq!(subdivided.triangles.len(), model.triangles.len() * 4);
+        assert!(subdivided.vertices.iter().all(|vertex| vertex.y == 0.));
+        assert_eq!(subdivided.aabb(), model.aabb());
+    }
+
+    #[test]
+    fn test_catmull_clark_subdivision() {
+        let cube = load_obj("models/cube.obj").expect("Failed to read model data");
+        let model = cube.subdivide(Subdivision::CatmullClark, 1);
+
+        assert_eq!(model.faces.len(), 6 * 4);
+        assert_eq!(model.triangles.len(), 6 * 4 * 2);
+        assert_eq!(model.uvs.len(), model.vertices.len());
+        assert_eq!(model.groups[0].triangles, 0..48);
+
+        // Each corner of the cube moves to (Q + 2R) / 3, where Q is the average of the centers of
+        // the faces around it and R the average of the midpoints of its edges
+        let corner = Vector3::new(5. / 9., 5. / 9., 5. / 9.);
+        assert!(model.vertices.iter().any(|&vertex| {
+            let diff = vertex - corner;
+            diff * diff < 1e-18
+        }));
+        assert!(model.vertices.iter().all(|vertex| vertex.x.abs() <= 1.));
+
+        let model = cube.subdivide(Subdivision::CatmullClark, 2);
+        assert_eq!(model.faces.len(), 6 * 4 * 4);
+    }
+}
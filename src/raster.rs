@@ -0,0 +1,1148 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::vector::{vector2::Vector2, vector3::Vector3};
+
+/// Bits of sub-pixel precision that the corners are snapped to
+pub const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_STEP: i64 = 1 << SUBPIXEL_BITS;
+/// Pixels are sampled at their centers
+const HALF_PIXEL: i64 = SUBPIXEL_STEP / 2;
+
+/// Farthest from the origin, in pixels, that a corner can be without overflowing the edge
+/// functions
+pub const GUARD_BAND: f64 = (1 << 20) as f64;
+/// Twice the area of the largest triangles whose weights are evaluated 4 pixels at a time. Edge
+/// functions up to it convert to floating point exactly without AVX-512
+#[cfg(target_arch = "x86_64")]
+const LANES_MAX_AREA: f64 = (1_u64 << 51) as f64;
+
+/// Triangle with its corners snapped to the fixed point sub-pixel grid. Edge functions are
+/// evaluated exactly on the grid, so triangles sharing an edge agree on which side each pixel
+/// center is, and ties are broken by the top-left rule: a center exactly on an edge belongs to
+/// the triangle below or to the right of it
+pub struct Triangle {
+    corners: [(i64, i64); 3],
+    /// Twice the signed area, in squared sub-pixel units
+    area: i64,
+}
+
+impl Triangle {
+    /// Snaps the corners, given in pixels. `None` if any of them is farther than the guard band
+    pub fn new(corners: [Vector2<f64>; 3]) -> Option<Self> {
+        if corners
+            .iter()
+            .any(|corner| !(corner.x.abs() <= GUARD_BAND && corner.y.abs() <= GUARD_BAND))
+        {
+            return None;
+        }
+
+        let corners = corners.map(|corner| {
+            (
+                (corner.x * SUBPIXEL_STEP as f64).round() as i64,
+                (corner.y * SUBPIXEL_STEP as f64).round() as i64,
+            )
+        });
+        let area = edge(corners[0], corners[1], corners[2]);
+
+        Some(Triangle { corners, area })
+    }
+
+    /// Twice the signed area, in squared sub-pixel units. Pixel rows go down, so it is negative
+    /// when the corners go counter clockwise on the screen, and 0 for degenerate triangles
+    pub fn signed_area(&self) -> i64 {
+        self.area
+    }
+
+    /// Columns and rows of the pixels whose centers may be inside the triangle
+    pub fn bounds(&self) -> (Range<i64>, Range<i64>) {
+        let min = |axis: fn(&(i64, i64)) -> i64| self.corners.iter().map(axis).min().unwrap();
+        let max = |axis: fn(&(i64, i64)) -> i64| self.corners.iter().map(axis).max().unwrap();
+        let first = |min: i64| (min - HALF_PIXEL + SUBPIXEL_STEP - 1).div_euclid(SUBPIXEL_STEP);
+        let last = |max: i64| (max - HALF_PIXEL).div_euclid(SUBPIXEL_STEP) + 1;
+
+        (
+            first(min(|p| p.0))..last(max(|p| p.0)),
+            first(min(|p| p.1))..last(max(|p| p.1)),
+        )
+    }
+
+    /// Calls `pixel` on each pixel of the given columns and rows whose center is covered, along
+    /// with the weights of the corners at that center. Both windings are covered
+    pub fn scan<F>(&self, columns: Range<usize>, rows: Range<usize>, pixel: F)
+    where
+        F: FnMut(usize, usize, Vector3<f64>),
+    {
+        self.scan_with(Evaluation::detect(), columns, rows, pixel);
+    }
+
+    /// [`Triangle::scan`] with the given evaluation, which falls back to
+    /// [`Evaluation::Scalar`] where the CPU does not support it
+    pub fn scan_with<F>(
+        &self,
+        evaluation: Evaluation,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        mut pixel: F,
+    ) where
+        F: FnMut(usize, usize, Vector3<f64>),
+    {
+        self.walk_rows(
+            evaluation,
+            columns,
+            rows,
+            |row, evaluation, y, values, columns| {
+                match evaluation {
+                    Evaluation::Scalar => row.scalar(y, values, columns, &mut pixel),
+                    // SAFETY: only walked with on CPUs that support AVX2
+                    #[cfg(target_arch = "x86_64")]
+                    Evaluation::Avx2 => unsafe { row.avx2(y, values, columns, &mut pixel) },
+                }
+            },
+        );
+    }
+
+    /// Like [`Triangle::scan`], testing the depth of each covered pixel against `depths`, which
+    /// holds the given rows, each as long as the end of the columns. Calls `pixel` on the pixels
+    /// that pass, along with the perspective correct weights of the corners, the depth, and
+    /// where it is stored
+    pub fn scan_depth<F>(
+        &self,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        depths: &mut [f32],
+        test: &DepthTest,
+        pixel: F,
+    ) where
+        F: FnMut(usize, usize, Vector3<f64>, f64, &mut f32),
+    {
+        self.scan_depth_with(Evaluation::detect(), columns, rows, depths, test, pixel);
+    }
+
+    /// [`Triangle::scan_depth`] with the given evaluation, which falls back to
+    /// [`Evaluation::Scalar`] where the CPU does not support it
+    pub fn scan_depth_with<F>(
+        &self,
+        evaluation: Evaluation,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        depths: &mut [f32],
+        test: &DepthTest,
+        mut pixel: F,
+    ) where
+        F: FnMut(usize, usize, Vector3<f64>, f64, &mut f32),
+    {
+        let (width, first_row) = (columns.end, rows.start);
+        self.walk_rows(
+            evaluation,
+            columns,
+            rows,
+            |row, evaluation, y, values, columns| {
+                let depths = &mut depths[(y - first_row) * width..][..width];
+                match evaluation {
+                    Evaluation::Scalar => {
+                        row.scalar_depth(y, values, columns, depths, test, &mut pixel)
+                    }
+                    // SAFETY: only walked with on CPUs that support AVX2
+                    #[cfg(target_arch = "x86_64")]
+                    Evaluation::Avx2 => unsafe {
+                        row.avx2_depth(y, values, columns, depths, test, &mut pixel)
+                    },
+                }
+            },
+        );
+    }
+
+    /// Calls `walk` on each row of the given columns and rows that the triangle may cover, with
+    /// the edge functions at the first of its columns and the evaluation to walk it with
+    fn walk_rows<W>(
+        &self,
+        evaluation: Evaluation,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        mut walk: W,
+    ) where
+        W: FnMut(&Row, Evaluation, usize, [i64; 3], Range<i64>),
+    {
+        if self.area == 0 {
+            return;
+        }
+
+        let (bounds_x, bounds_y) = self.bounds();
+        let start_x = bounds_x.start.max(columns.start as i64);
+        let end_x = bounds_x.end.min(columns.end as i64);
+        let start_y = bounds_y.start.max(rows.start as i64);
+        let end_y = bounds_y.end.min(rows.end as i64);
+        if start_x >= end_x || start_y >= end_y {
+            return;
+        }
+
+        let (row, mut row_values, row_steps) = self.edges_at(start_x, start_y);
+
+        // Rows too short to fill the lanes are faster to walk one pixel at a time
+        let evaluation = match end_x - start_x {
+            ..8 => Evaluation::Scalar,
+            _ => evaluation.supported(),
+        };
+
+        for y in start_y..end_y {
+            walk(&row, evaluation, y as usize, row_values, start_x..end_x);
+
+            for idx in 0..3 {
+                row_values[idx] += row_steps[idx];
+            }
+        }
+    }
+
+    /// Like [`Triangle::scan`], for pixels with a sample at each of the `offsets` from their
+    /// center, in 1/16 of a pixel. Calls `pixel` on each pixel with at least one covered sample,
+    /// along with the mask of those samples and the weights of the corners at the pixel center
+    pub fn scan_samples<F>(
+        &self,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        offsets: &[(i64, i64)],
+        mut pixel: F,
+    ) where
+        F: FnMut(usize, usize, u32, Vector3<f64>),
+    {
+        if self.area == 0 {
+            return;
+        }
+
+        // Samples are less than half a pixel away from the centers
+        let (bounds_x, bounds_y) = self.bounds();
+        let start_x = (bounds_x.start - 1).max(columns.start as i64);
+        let end_x = (bounds_x.end + 1).min(columns.end as i64);
+        let start_y = (bounds_y.start - 1).max(rows.start as i64);
+        let end_y = (bounds_y.end + 1).min(rows.end as i64);
+        if start_x >= end_x || start_y >= end_y {
+            return;
+        }
+
+        let (row, mut row_values, row_steps) = self.edges_at(start_x, start_y);
+
+        // Change of the edge functions from the center of a pixel to each of its samples
+        let sample_steps: Vec<[i64; 3]> = offsets
+            .iter()
+            .map(|&(x, y)| [0, 1, 2].map(|idx| (row.steps[idx] * x + row_steps[idx] * y) / 16))
+            .collect();
+
+        for y in start_y..end_y {
+            let mut values = row_values;
+            for x in start_x..end_x {
+                let mask = sample_steps
+                    .iter()
+                    .enumerate()
+                    .fold(0, |mask, (sample, steps)| {
+                        let inside = (values[0] + steps[0])
+                            | (values[1] + steps[1])
+                            | (values[2] + steps[2])
+                            >= 0;
+                        mask | (inside as u32) << sample
+                    });
+                if mask != 0 {
+                    pixel(x as usize, y as usize, mask, row.weights(values));
+                }
+
+                for (value, step) in values.iter_mut().zip(row.steps) {
+                    *value += step;
+                }
+            }
+
+            for (value, step) in row_values.iter_mut().zip(row_steps) {
+                *value += step;
+            }
+        }
+    }
+
+    /// Change of the weights of the corners from a pixel to the next one in its row, and to the
+    /// one below it
+    pub fn gradients(&self) -> (Vector3<f64>, Vector3<f64>) {
+        let (row, _, row_steps) = self.edges_at(0, 0);
+        let gradient =
+            |steps: [i64; 3]| Vector3::new(steps[0] as f64, steps[1] as f64, steps[2] as f64);
+
+        (
+            gradient(row.steps) / row.area,
+            gradient(row_steps) / row.area,
+        )
+    }
+
+    /// Edge functions at the center of pixel `x`, `y`, along with their change per column and
+    /// per row. The values include a bias that keeps centers exactly on edges which are not top
+    /// or left edges outside
+    fn edges_at(&self, x: i64, y: i64) -> (Row, [i64; 3], [i64; 3]) {
+        // Flip the edges of negative triangles, so that the inside is positive for both windings
+        let sign = self.area.signum();
+        let [a, b, c] = self.corners;
+
+        // Edge opposite to each corner, starting from the one after it
+        let edges = [(b, c), (c, a), (a, b)];
+
+        let origin = (
+            x * SUBPIXEL_STEP + HALF_PIXEL,
+            y * SUBPIXEL_STEP + HALF_PIXEL,
+        );
+
+        let mut row_values = [0; 3];
+        let mut row = Row {
+            steps: [0; 3],
+            biases: [0; 3],
+            area: (self.area * sign) as f64,
+        };
+        let mut row_steps = [0; 3];
+        for (idx, &(from, to)) in edges.iter().enumerate() {
+            row.steps[idx] = (from.1 - to.1) * sign * SUBPIXEL_STEP;
+            row_steps[idx] = (to.0 - from.0) * sign * SUBPIXEL_STEP;
+
+            // The inside is right of left edges, and below horizontal top edges
+            let top_left = row.steps[idx] > 0 || (row.steps[idx] == 0 && row_steps[idx] > 0);
+            row.biases[idx] = if top_left { 0 } else { -1 };
+            row_values[idx] = edge(from, to, origin) * sign + row.biases[idx];
+        }
+
+        (row, row_values, row_steps)
+    }
+}
+
+/// Calls `pixel` on each pixel of the given columns and rows that a line `width` pixels wide,
+/// with round ends, covers between `from` and `to`. Along with how much of the pixel center's
+/// neighbourhood it covers, from 0 to 1, which smooths its edges, and how far along the
+/// segment the closest point to the center is, from 0 to 1
+pub fn line<F>(
+    from: Vector2<f64>,
+    to: Vector2<f64>,
+    width: f64,
+    columns: Range<usize>,
+    rows: Range<usize>,
+    mut pixel: F,
+) where
+    F: FnMut(usize, usize, f64, f64),
+{
+    let half_width = width / 2.;
+    let direction = to - from;
+    let length_squared = direction.length_squared();
+
+    // Walk along the longest axis, each step covering the pixels that are close enough on the
+    // other axis
+    let x_major = direction.x.abs() >= direction.y.abs();
+    let (from_major, from_minor) = major_minor(x_major, from);
+    let (to_major, _) = major_minor(x_major, to);
+    let (major_range, minor_range) = match x_major {
+        true => (columns, rows),
+        false => (rows, columns),
+    };
+    let delta_major = to_major - from_major;
+    let slope = if delta_major == 0. {
+        0.
+    } else {
+        major_minor(x_major, direction).1 / delta_major
+    };
+
+    // Pixels whose center is farther than this from the line are not covered at all
+    let reach = half_width + 1.;
+    let spread = reach * (1. + slope * slope).sqrt();
+    let clamp_range = |start: f64, end: f64, range: &Range<usize>| {
+        let start = (start.floor().max(range.start as f64) as usize).min(range.end);
+        let end = ((end.floor() + 1.).max(start as f64) as usize).min(range.end);
+        start..end
+    };
+
+    let (first_major, last_major) = (from_major.min(to_major), from_major.max(to_major));
+    for major in clamp_range(first_major - reach, last_major + reach, &major_range) {
+        // Where the line crosses the middle of this step, stopping at its ends
+        let center = (major as f64 + 0.5).clamp(first_major, last_major);
+        let crossing = from_minor + (center - from_major) * slope;
+
+        for minor in clamp_range(crossing - spread, crossing + spread, &minor_range) {
+            let (x, y) = match x_major {
+                true => (major, minor),
+                false => (minor, major),
+            };
+
+            let center = Vector2::new(x as f64 + 0.5, y as f64 + 0.5);
+            let along = if length_squared == 0. {
+                0.
+            } else {
+                ((center - from) * direction / length_squared).clamp(0., 1.)
+            };
+            let distance = center.distance(&from.lerp(&to, along));
+
+            let coverage = (half_width + 0.5 - distance).clamp(0., 1.);
+            if coverage > 0. {
+                pixel(x, y, coverage, along);
+            }
+        }
+    }
+}
+
+/// Calls `pixel` on each pixel of a one pixel wide line from `from` to `to`, both included,
+/// with Bresenham's algorithm
+pub fn bresenham<F>(from: (i64, i64), to: (i64, i64), mut pixel: F)
+where
+    F: FnMut(i64, i64),
+{
+    let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+    let (step_x, step_y) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+
+    // Error of the next pixel, stepping on both axes when it is small enough on each
+    let (mut x, mut y) = from;
+    let mut error = dx + dy;
+    loop {
+        pixel(x, y);
+        if (x, y) == to {
+            break;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Calls `pixel` on the pixels next to a line from `from` to `to`, with Xiaolin Wu's
+/// algorithm. Along with how much of the pixel the line covers, from 0 to 1, splitting each
+/// step between the two pixels closest to the line
+pub fn wu_line<F>(from: Vector2<f64>, to: Vector2<f64>, mut pixel: F)
+where
+    F: FnMut(i64, i64, f64),
+{
+    // Walk along the longest axis from left to right, in coordinates where pixel centers are
+    // whole numbers
+    let steep = (to.y - from.y).abs() > (to.x - from.x).abs();
+    let center = |point: Vector2<f64>| {
+        let (major, minor) = major_minor(!steep, point);
+        (major - 0.5, minor - 0.5)
+    };
+    let (mut start, mut end) = (center(from), center(to));
+    if start.0 > end.0 {
+        (start, end) = (end, start);
+    }
+
+    let mut plot = |major: i64, minor: i64, coverage: f64| {
+        if coverage > 0. {
+            match steep {
+                true => pixel(minor, major, coverage),
+                false => pixel(major, minor, coverage),
+            }
+        }
+    };
+
+    let delta = end.0 - start.0;
+    let gradient = if delta == 0. {
+        1.
+    } else {
+        (end.1 - start.1) / delta
+    };
+
+    // Splits a step between the pixels on both sides of where the line crosses it
+    let fraction = |value: f64| value - value.floor();
+    let mut step = |major: i64, crossing: f64, coverage: f64| {
+        plot(
+            major,
+            crossing.floor() as i64,
+            (1. - fraction(crossing)) * coverage,
+        );
+        plot(
+            major,
+            crossing.floor() as i64 + 1,
+            fraction(crossing) * coverage,
+        );
+    };
+
+    // The ends only cover the part of their step that is on the line
+    let first = (start.0 + 0.5).floor();
+    let crossing = start.1 + gradient * (first - start.0);
+    step(first as i64, crossing, 1. - fraction(start.0 + 0.5));
+    let last = (end.0 + 0.5).floor();
+    step(
+        last as i64,
+        end.1 + gradient * (last - end.0),
+        fraction(end.0 + 0.5),
+    );
+
+    let mut crossing = crossing + gradient;
+    for major in first as i64 + 1..last as i64 {
+        step(major, crossing, 1.);
+        crossing += gradient;
+    }
+}
+
+/// Calls `point` on the pixels of one eighth of a circle of `radius` pixels around the origin,
+/// with the midpoint algorithm. From `(radius, 0)` to the diagonal, so that `x >= y`
+pub fn circle_octant<F>(radius: i64, mut point: F)
+where
+    F: FnMut(i64, i64),
+{
+    let (mut x, mut y) = (radius, 0);
+    let mut error = 1 - radius;
+    while x >= y {
+        point(x, y);
+
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+}
+
+/// Calls `span` on each row of the given rows that the polygon with the given corners covers,
+/// with the columns whose pixel centers are inside of it. Overlapping parts of the outline
+/// cancel each other out (even-odd rule)
+pub fn polygon<F>(corners: &[Vector2<f64>], rows: Range<usize>, mut span: F)
+where
+    F: FnMut(usize, Range<i64>),
+{
+    let (top, bottom) = corners.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(top, bottom), corner| (top.min(corner.y), bottom.max(corner.y)),
+    );
+    if corners.len() < 3 || top >= bottom {
+        return;
+    }
+
+    let first = (top.floor().max(rows.start as f64) as usize).min(rows.end);
+    let last = ((bottom.ceil().max(0.) as usize).min(rows.end)).max(first);
+
+    let mut crossings = Vec::new();
+    for y in first..last {
+        // Where the edges cross the middle of the row
+        let middle = y as f64 + 0.5;
+        crossings.clear();
+        for (idx, from) in corners.iter().enumerate() {
+            let to = corners[(idx + 1) % corners.len()];
+            if (from.y <= middle) != (to.y <= middle) {
+                crossings.push(from.x + (middle - from.y) * (to.x - from.x) / (to.y - from.y));
+            }
+        }
+        crossings.sort_unstable_by(f64::total_cmp);
+
+        for pair in crossings.chunks_exact(2) {
+            let columns = (pair[0] - 0.5).ceil() as i64..(pair[1] - 0.5).ceil() as i64;
+            if !columns.is_empty() {
+                span(y, columns);
+            }
+        }
+    }
+}
+
+/// Coordinates of a point along the longest axis of a line, and along the other one
+fn major_minor(x_major: bool, point: Vector2<f64>) -> (f64, f64) {
+    match x_major {
+        true => (point.x, point.y),
+        false => (point.y, point.x),
+    }
+}
+
+/// Positions of the samples of a pixel for multisampling, in 1/16 of a pixel from its center.
+/// `None` for unsupported sample counts
+pub fn sample_offsets(samples: usize) -> Option<&'static [(i64, i64)]> {
+    // Rotated grids, which keep every sample on its own row and column
+    match samples {
+        1 => Some(&[(0, 0)]),
+        2 => Some(&[(4, 4), (-4, -4)]),
+        4 => Some(&[(-2, -6), (6, -2), (-6, 2), (2, 6)]),
+        8 => Some(&[
+            (1, -3),
+            (-1, 3),
+            (5, 1),
+            (-3, -5),
+            (-5, 5),
+            (-7, -1),
+            (3, 7),
+            (7, -7),
+        ]),
+        _ => None,
+    }
+}
+
+/// Instructions that the edge functions are evaluated with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evaluation {
+    /// One pixel at a time
+    Scalar,
+    /// 4 pixels at a time
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+}
+
+impl Evaluation {
+    /// Fastest evaluation supported by the CPU
+    pub fn detect() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            return Evaluation::Avx2;
+        }
+
+        Evaluation::Scalar
+    }
+
+    /// This evaluation if the CPU supports it, [`Evaluation::Scalar`] otherwise
+    fn supported(self) -> Self {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Evaluation::Avx2 if !is_x86_feature_detected!("avx2") => Evaluation::Scalar,
+            _ => self,
+        }
+    }
+}
+
+/// What the depth of the pixels of a triangle is interpolated from, and the depths that are
+/// kept
+#[derive(Clone, Debug)]
+pub struct DepthTest {
+    /// Inverse of the clip space w of each corner, that the weights are corrected with
+    pub inverse_w: Vector3<f64>,
+    /// View space depth of each corner
+    pub depths: Vector3<f64>,
+    /// Depths outside of it are clipped
+    pub range: RangeInclusive<f64>,
+}
+
+impl DepthTest {
+    /// Perspective correct weights of the corners and depth at a pixel, from the weights of
+    /// the corners on the screen
+    #[inline]
+    pub fn at(&self, weights: Vector3<f64>) -> (Vector3<f64>, f64) {
+        let x = weights.x * self.inverse_w.x;
+        let y = weights.y * self.inverse_w.y;
+        let z = weights.z * self.inverse_w.z;
+        let sum = x + y + z;
+
+        let weights = Vector3::new(x / sum, y / sum, z / sum);
+        let depth =
+            weights.x * self.depths.x + weights.y * self.depths.y + weights.z * self.depths.z;
+        (weights, depth)
+    }
+
+    /// Whether a pixel at `depth` is kept, in front of the `stored` one or at the same depth
+    #[inline]
+    fn passes(&self, depth: f64, stored: f32) -> bool {
+        self.range.contains(&depth) && depth as f32 >= stored
+    }
+}
+
+/// What the rows of a triangle share when walking along them
+struct Row {
+    /// Change of the edge functions per column
+    steps: [i64; 3],
+    /// Added to the edge functions to apply the top-left rule
+    biases: [i64; 3],
+    /// Twice the area of the triangle, positive
+    area: f64,
+}
+
+impl Row {
+    /// Weights of the corners at a pixel center, from the biased edge function values there
+    #[inline]
+    fn weights(&self, values: [i64; 3]) -> Vector3<f64> {
+        Vector3::new(
+            (values[0] - self.biases[0]) as f64,
+            (values[1] - self.biases[1]) as f64,
+            (values[2] - self.biases[2]) as f64,
+        ) / self.area
+    }
+
+    /// Walks the columns of row `y` one pixel at a time, `values` are the edge functions at the
+    /// first column
+    #[inline]
+    fn scalar<F>(&self, y: usize, mut values: [i64; 3], columns: Range<i64>, pixel: &mut F)
+    where
+        F: FnMut(usize, usize, Vector3<f64>),
+    {
+        for x in columns {
+            // Inside when no value has its sign bit set
+            if values[0] | values[1] | values[2] >= 0 {
+                pixel(x as usize, y, self.weights(values));
+            }
+
+            for (value, step) in values.iter_mut().zip(self.steps) {
+                *value += step;
+            }
+        }
+    }
+
+    /// Same as [`Row::scalar`], testing the depth of the covered pixels
+    #[inline]
+    fn scalar_depth<F>(
+        &self,
+        y: usize,
+        values: [i64; 3],
+        columns: Range<i64>,
+        depths: &mut [f32],
+        test: &DepthTest,
+        pixel: &mut F,
+    ) where
+        F: FnMut(usize, usize, Vector3<f64>, f64, &mut f32),
+    {
+        self.scalar(y, values, columns, &mut |x, y, weights| {
+            let (weights, depth) = test.at(weights);
+            if test.passes(depth, depths[x]) {
+                pixel(x, y, weights, depth, &mut depths[x]);
+            }
+        });
+    }
+
+    /// Same as [`Row::scalar`], 4 pixels at a time
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    fn avx2<F>(&self, y: usize, values: [i64; 3], columns: Range<i64>, pixel: &mut F)
+    where
+        F: FnMut(usize, usize, Vector3<f64>),
+    {
+        let (values, rest) = self.avx2_groups(values, columns.clone(), |x, covered, weights| {
+            let [a, b, c] = weights.map(lanes);
+            for lane in 0..4 {
+                if covered & (1 << lane) != 0 {
+                    let weights = Vector3::new(a[lane], b[lane], c[lane]);
+                    pixel(x + lane, y, weights);
+                }
+            }
+        });
+
+        self.scalar(y, values, rest..columns.end, pixel);
+    }
+
+    /// Same as [`Row::scalar_depth`], 4 pixels at a time. The weights and depth go through the
+    /// same operations in the same order, so the pixels that pass are identical
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    fn avx2_depth<F>(
+        &self,
+        y: usize,
+        values: [i64; 3],
+        columns: Range<i64>,
+        depths: &mut [f32],
+        test: &DepthTest,
+        pixel: &mut F,
+    ) where
+        F: FnMut(usize, usize, Vector3<f64>, f64, &mut f32),
+    {
+        use std::arch::x86_64::*;
+
+        let inverse_w = [test.inverse_w.x, test.inverse_w.y, test.inverse_w.z]
+            .map(|value| _mm256_set1_pd(value));
+        let corner_depths =
+            [test.depths.x, test.depths.y, test.depths.z].map(|value| _mm256_set1_pd(value));
+        let near = _mm256_set1_pd(*test.range.end());
+        let far = _mm256_set1_pd(*test.range.start());
+
+        let (values, rest) = self.avx2_groups(values, columns.clone(), |x, covered, weights| {
+            let [a, b, c] = [0, 1, 2].map(|idx| _mm256_mul_pd(weights[idx], inverse_w[idx]));
+            let sum = _mm256_add_pd(_mm256_add_pd(a, b), c);
+            let weights = [a, b, c].map(|weight| _mm256_div_pd(weight, sum));
+            let depth = _mm256_add_pd(
+                _mm256_add_pd(
+                    _mm256_mul_pd(weights[0], corner_depths[0]),
+                    _mm256_mul_pd(weights[1], corner_depths[1]),
+                ),
+                _mm256_mul_pd(weights[2], corner_depths[2]),
+            );
+
+            let kept = _mm256_and_pd(
+                _mm256_cmp_pd::<_CMP_GE_OQ>(depth, far),
+                _mm256_cmp_pd::<_CMP_LE_OQ>(depth, near),
+            );
+            // SAFETY: the group's 4 columns are in the row
+            let stored = unsafe { _mm_loadu_ps(depths[x..x + 4].as_ptr()) };
+            let in_front = _mm_cmp_ps::<_CMP_GE_OQ>(_mm256_cvtpd_ps(depth), stored);
+            let passed = covered & _mm256_movemask_pd(kept) & _mm_movemask_ps(in_front);
+            if passed == 0 {
+                return;
+            }
+
+            let [a, b, c] = weights.map(lanes);
+            let depth = lanes(depth);
+            for lane in 0..4 {
+                if passed & (1 << lane) != 0 {
+                    let weights = Vector3::new(a[lane], b[lane], c[lane]);
+                    pixel(x + lane, y, weights, depth[lane], &mut depths[x + lane]);
+                }
+            }
+        });
+
+        self.scalar_depth(y, values, rest..columns.end, depths, test, pixel);
+    }
+
+    /// Walks `columns` 4 pixels at a time, as long as 4 of them are left. Calls `group` with the
+    /// first column of each group with covered pixels, one bit per covered pixel, and the
+    /// weights of the corners at each of them. Returns the edge functions at the first column
+    /// that is left. The edge functions stay exact 64 bit integers, so the coverage and weights
+    /// are identical to [`Row::scalar`]
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    fn avx2_groups<G>(
+        &self,
+        mut values: [i64; 3],
+        columns: Range<i64>,
+        mut group: G,
+    ) -> ([i64; 3], i64)
+    where
+        G: FnMut(usize, i32, [std::arch::x86_64::__m256d; 3]),
+    {
+        use std::arch::x86_64::*;
+
+        if self.area >= LANES_MAX_AREA {
+            return (values, columns.start);
+        }
+
+        // Each lane is one column further than the previous one
+        let spread = |idx: usize| {
+            let (value, step) = (values[idx], self.steps[idx]);
+            _mm256_set_epi64x(value + 3 * step, value + 2 * step, value + step, value)
+        };
+        let mut lane_values = [spread(0), spread(1), spread(2)];
+        let lane_steps = self.steps.map(|step| _mm256_set1_epi64x(4 * step));
+        let biases = self.biases.map(|bias| _mm256_set1_epi64x(bias));
+        let area = _mm256_set1_pd(self.area);
+
+        // Adding an integer below 2^51 to the bits of 2^52 + 2^51 puts it in the low bits of
+        // the mantissa
+        let magic = _mm256_set1_epi64x(0x4338_0000_0000_0000);
+        let to_f64 = |value: __m256i| {
+            let shifted = _mm256_castsi256_pd(_mm256_add_epi64(value, magic));
+            _mm256_sub_pd(shifted, _mm256_castsi256_pd(magic))
+        };
+
+        let mut x = columns.start;
+        while x + 4 <= columns.end {
+            // One bit per lane, set for pixels outside of the triangle
+            let signs = _mm256_or_si256(
+                _mm256_or_si256(lane_values[0], lane_values[1]),
+                lane_values[2],
+            );
+            let outside = _mm256_movemask_pd(_mm256_castsi256_pd(signs));
+
+            // Covered edge functions are between 0 and the area, so they convert exactly
+            if outside != 0b1111 {
+                let weights = [0, 1, 2].map(|idx| {
+                    let value = _mm256_sub_epi64(lane_values[idx], biases[idx]);
+                    _mm256_div_pd(to_f64(value), area)
+                });
+                group(x as usize, !outside & 0b1111, weights);
+            }
+
+            for (lane_value, lane_step) in lane_values.iter_mut().zip(lane_steps) {
+                *lane_value = _mm256_add_epi64(*lane_value, lane_step);
+            }
+            for (value, step) in values.iter_mut().zip(self.steps) {
+                *value += 4 * step;
+            }
+            x += 4;
+        }
+
+        (values, x)
+    }
+}
+
+/// Values of the lanes of a vector, from the first column to the last
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn lanes(vector: std::arch::x86_64::__m256d) -> [f64; 4] {
+    // SAFETY: both are 4 packed f64
+    unsafe { std::mem::transmute(vector) }
+}
+
+/// Twice the signed area of the triangle `from`, `to`, `point`, positive if `point` is on the
+/// right of the edge when looking from `from` to `to`, with rows going down
+fn edge(from: (i64, i64), to: (i64, i64), point: (i64, i64)) -> i64 {
+    (to.0 - from.0) * (point.1 - from.1) - (to.1 - from.1) * (point.0 - from.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Number of times each pixel of a `size` by `size` grid is covered by the triangles
+    fn coverage(triangles: &[[Vector2<f64>; 3]], size: usize) -> Vec<Vec<usize>> {
+        let mut counts = vec![vec![0; size]; size];
+        for &corners in triangles {
+            Triangle::new(corners)
+                .unwrap()
+                .scan(0..size, 0..size, |x, y, _| counts[y][x] += 1);
+        }
+        counts
+    }
+
+    #[test]
+    fn test_pixel_centers() {
+        let square = |min: f64, max: f64| {
+            let [a, b, c, d] =
+                [(min, min), (max, min), (max, max), (min, max)].map(|(x, y)| Vector2::new(x, y));
+            [[a, b, c], [a, c, d]]
+        };
+
+        // The last row and column are included, and only the centers count
+        let counts = coverage(&square(0., 2.), 4);
+        assert_eq!(counts.iter().flatten().sum::<usize>(), 4);
+        assert!((0..2).all(|y| (0..2).all(|x| counts[y][x] == 1)));
+
+        // Edges through the centers keep the top and left ones only
+        let counts = coverage(&square(0.5, 2.5), 4);
+        assert_eq!(counts.iter().flatten().sum::<usize>(), 4);
+        assert!((0..2).all(|y| (0..2).all(|x| counts[y][x] == 1)));
+    }
+
+    #[test]
+    fn test_shared_edges() {
+        // A fan around a center, with edges crossing pixel centers at all angles, including
+        // horizontal and vertical ones, and a corner right on a center
+        let center = Vector2::new(8.5, 8.5);
+        let rim: Vec<Vector2<f64>> = [
+            (0.5, 0.5),
+            (8.5, 0.25),
+            (16.5, 0.5),
+            (16.5, 8.5),
+            (15.9, 16.1),
+            (8.5, 16.5),
+            (0.5, 16.5),
+            (0.5, 11.3),
+        ]
+        .iter()
+        .map(|&(x, y)| Vector2::new(x, y))
+        .collect();
+
+        // Both windings, so that the ties are broken the same way for back faces
+        for reverse in [false, true] {
+            let triangles: Vec<[Vector2<f64>; 3]> = (0..rim.len())
+                .map(|idx| {
+                    let (a, b) = (rim[idx], rim[(idx + 1) % rim.len()]);
+                    if reverse {
+                        [center, b, a]
+                    } else {
+                        [center, a, b]
+                    }
+                })
+                .collect();
+
+            let counts = coverage(&triangles, 18);
+            assert!(counts.iter().flatten().all(|&count| count <= 1));
+
+            // Every pixel strictly inside the fan is covered exactly once
+            assert!((1..16).all(|y| (1..16).all(|x| counts[y][x] == 1)));
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_evaluations() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        if !is_x86_feature_detected!("avx2") {
+            return;
+        }
+
+        // Every pixel and weight that the triangle covers in a 37 by 37 grid, whose width leaves
+        // columns past the last group of 4
+        let covered = |triangle: &Triangle, evaluation: Evaluation| {
+            let mut pixels = Vec::new();
+            triangle.scan_with(evaluation, 0..37, 0..37, |x, y, weights| {
+                pixels.push((x, y, weights))
+            });
+            pixels
+        };
+
+        // Every pixel, weight and depth that passes the depth test, and the depths after them
+        let passed =
+            |triangle: &Triangle, evaluation: Evaluation, test: &DepthTest, depths: &[f32]| {
+                let mut depths = depths.to_vec();
+                let mut pixels = Vec::new();
+                triangle.scan_depth_with(
+                    evaluation,
+                    0..37,
+                    0..37,
+                    &mut depths,
+                    test,
+                    |x, y, weights, depth, stored| {
+                        pixels.push((x, y, weights, depth));
+                        *stored = depth as f32;
+                    },
+                );
+                (pixels, depths)
+            };
+
+        // Triangles of all sizes and windings, some of them partly outside of the grid, going
+        // through what is already drawn and the near and far planes
+        let mut rng = StdRng::seed_from_u64(7);
+        let depths: Vec<f32> = (0..37 * 37)
+            .map(|_| match rng.random_bool(0.5) {
+                true => rng.random_range(-9.0..-1.),
+                false => f32::NEG_INFINITY,
+            })
+            .collect();
+        for _ in 0..200 {
+            let corners = [(); 3]
+                .map(|_| Vector2::new(rng.random_range(-8.0..45.), rng.random_range(-8.0..45.)));
+            let triangle = Triangle::new(corners).unwrap();
+            let [inverse_w, corner_depths] = [(0.1, 2.), (-10., -1.)].map(|(min, max)| {
+                let [x, y, z] = [(); 3].map(|_| rng.random_range(min..max));
+                Vector3::new(x, y, z)
+            });
+            let test = DepthTest {
+                inverse_w,
+                depths: corner_depths,
+                range: -8.0..=-2.,
+            };
+
+            let pixels = covered(&triangle, Evaluation::Scalar);
+            assert_eq!(pixels, covered(&triangle, Evaluation::Avx2));
+            let pixels = passed(&triangle, Evaluation::Scalar, &test, &depths);
+            assert_eq!(pixels, passed(&triangle, Evaluation::Avx2, &test, &depths));
+        }
+
+        // Too large for the weights to convert exactly 4 pixels at a time
+        let corners = [(-1e6, -1e6), (1e6, -1e6), (0., 1e6)].map(|(x, y)| Vector2::new(x, y));
+        let triangle = Triangle::new(corners).unwrap();
+        let pixels = covered(&triangle, Evaluation::Scalar);
+        assert_eq!(pixels.len(), 37 * 37);
+        assert_eq!(pixels, covered(&triangle, Evaluation::Avx2));
+    }
+
+    #[test]
+    fn test_samples() {
+        // A fan of triangles sharing edges, as in test_shared_edges
+        let center = Vector2::new(8.3, 8.6);
+        let rim =
+            [(0.5, 0.5), (16.5, 0.25), (16.1, 16.5), (0.5, 16.5)].map(|(x, y)| Vector2::new(x, y));
+        let triangles: Vec<Triangle> = (0..rim.len())
+            .map(|idx| Triangle::new([center, rim[idx], rim[(idx + 1) % rim.len()]]).unwrap())
+            .collect();
+
+        // A single sample at the center covers the same pixels
+        for triangle in &triangles {
+            let mut centers = Vec::new();
+            triangle.scan(0..18, 0..18, |x, y, _| centers.push((x, y)));
+            let mut samples = Vec::new();
+            triangle.scan_samples(0..18, 0..18, &[(0, 0)], |x, y, mask, _| {
+                assert_eq!(mask, 1);
+                samples.push((x, y));
+            });
+            assert_eq!(centers, samples);
+        }
+
+        // Every sample is covered at most once, and all of them inside of the fan
+        let offsets = sample_offsets(4).unwrap();
+        let mut counts = vec![vec![[0; 4]; 18]; 18];
+        for triangle in &triangles {
+            triangle.scan_samples(0..18, 0..18, offsets, |x, y, mask, _| {
+                for (sample, count) in counts[y][x].iter_mut().enumerate() {
+                    *count += (mask >> sample) & 1;
+                }
+            });
+        }
+        assert!(counts.iter().flatten().flatten().all(|&count| count <= 1));
+        assert!((1..16).all(|y| (1..16).all(|x| counts[y][x] == [1; 4])));
+        assert!(counts[0][0].contains(&0) && counts[0][0].contains(&1));
+    }
+
+    #[test]
+    fn test_weights() {
+        let triangle = Triangle::new([
+            Vector2::new(0., 0.),
+            Vector2::new(8., 0.),
+            Vector2::new(0., 8.),
+        ])
+        .unwrap();
+        assert!(triangle.signed_area() > 0);
+
+        let mut weights = None;
+        triangle.scan(1..2, 1..2, |_, _, pixel_weights| {
+            weights = Some(pixel_weights)
+        });
+        assert_eq!(weights, Some(Vector3::new(0.625, 0.1875, 0.1875)));
+        assert_eq!(
+            triangle.gradients(),
+            (
+                Vector3::new(-0.125, 0.125, 0.),
+                Vector3::new(-0.125, 0., 0.125)
+            )
+        );
+
+        assert!(Triangle::new([Vector2::new(GUARD_BAND * 2., 0.); 3]).is_none());
+    }
+
+    #[test]
+    fn test_line() {
+        let mut pixels = Vec::new();
+        line(
+            Vector2::new(1., 2.5),
+            Vector2::new(9., 2.5),
+            1.,
+            0..8,
+            0..5,
+            |x, y, coverage, along| pixels.push((x, y, coverage, along)),
+        );
+
+        // A horizontal line through the pixel centers of a row, cut by the columns
+        for (x, y, coverage, along) in pixels {
+            assert_eq!(y, 2);
+            assert!(x < 8);
+            assert_eq!(coverage, if x == 0 { 0.5 } else { 1. });
+            assert_eq!(along, ((x as f64 - 0.5) / 8.).max(0.));
+        }
+
+        // Round ends, and coverage fading with the distance to the line
+        let mut covered = crate::buffer::Buffer::new(8, 8, 0.);
+        let point = Vector2::new(4.5, 4.5);
+        line(point, point, 2., 0..8, 0..8, |x, y, coverage, _| {
+            covered[(x, y)] = coverage
+        });
+        assert_eq!(covered[(4, 4)], 1.);
+        assert_eq!(covered[(3, 4)], 0.5);
+        assert_eq!(covered[(2, 2)], 0.);
+    }
+
+    #[test]
+    fn test_line_algorithms() {
+        // Every step moves by one pixel on the longest axis, ending exactly on the last pixel
+        let mut pixels = Vec::new();
+        bresenham((0, 0), (6, -2), |x, y| pixels.push((x, y)));
+        assert_eq!(
+            pixels,
+            vec![(0, 0), (1, 0), (2, -1), (3, -1), (4, -1), (5, -2), (6, -2)]
+        );
+
+        // The coverage of each step of a smooth line adds up to one, away from its ends
+        let mut columns = [0.; 10];
+        wu_line(
+            Vector2::new(0.5, 0.5),
+            Vector2::new(9.5, 4.),
+            |x, _, coverage| columns[x as usize] += coverage,
+        );
+        for total in &columns[1..9] {
+            assert!((total - 1.).abs() < 1e-9);
+        }
+
+        // The octant of a circle ends on its diagonal
+        let mut octant = Vec::new();
+        circle_octant(3, |x, y| octant.push((x, y)));
+        assert_eq!(octant, vec![(3, 0), (3, 1), (2, 2)]);
+
+        // A square of 4 by 4 pixels with a hole of 2 by 2 in the middle, joined to its outline
+        let square = [(0., 0.), (4., 0.), (4., 4.), (0., 4.)].map(|(x, y)| Vector2::new(x, y));
+        let hole = square.map(|corner| (corner + Vector2::new(2., 2.)) * 0.5);
+        let corners = [square.as_slice(), &[square[0]], &hole, &[hole[0]]].concat();
+        let mut spans = Vec::new();
+        polygon(&corners, 0..10, |y, columns| spans.push((y, columns)));
+        assert_eq!(
+            spans,
+            vec![
+                (0, 0..4),
+                (1, 0..1),
+                (1, 3..4),
+                (2, 0..1),
+                (2, 3..4),
+                (3, 0..4)
+            ]
+        );
+    }
+}
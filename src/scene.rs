@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use crate::{
+    camera::Camera,
+    model::{Model, bounds::Aabb},
+    screen::{BlendMode, Color},
+    texture::Texture,
+    vector::{matrix4::Matrix4, transform::Transform},
+};
+
+/// Surface of the models of a node, applied on top of their own colors
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    /// Multiplied with the face colors of the model
+    pub color: Color,
+    /// Replaces the texture of the model, if it has texture coordinates
+    pub texture: Option<Arc<Texture>>,
+    /// How the model is combined with what is behind it
+    pub blend: BlendMode,
+    /// Whether the model hides what is drawn after it. Usually off for transparent materials,
+    /// so that they don't hide each other
+    pub depth_write: bool,
+}
+
+impl Material {
+    /// Material that lets what is behind it show through, by the alpha of its colors
+    pub fn transparent(color: Color) -> Self {
+        Material {
+            color,
+            blend: BlendMode::Alpha,
+            depth_write: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            color: Color::new(0xff, 0xff, 0xff, 0xff),
+            texture: None,
+            blend: BlendMode::Opaque,
+            depth_write: true,
+        }
+    }
+}
+
+/// Object placed relative to its parent, or to the world for the nodes at the root of a scene
+#[derive(Clone, Default)]
+pub struct Node {
+    pub name: String,
+    pub transform: Transform,
+    /// Shared so that a model can be placed many times without copying it
+    pub model: Option<Arc<Model>>,
+    /// Material of the model and of the children without one
+    pub material: Option<Material>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn new(transform: Transform) -> Self {
+        Node {
+            transform,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<Arc<Model>>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    pub fn with_child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Depth first search for the first node with the given name, including this one
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Node> {
+        if self.name == name {
+            return Some(self);
+        }
+
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(name))
+    }
+
+    fn walk<'a, F>(&'a self, parent: &Matrix4, material: Option<&'a Material>, f: &mut F)
+    where
+        F: FnMut(&'a Node, &Matrix4, Option<&'a Material>),
+    {
+        let world = *parent * self.transform.to_matrix();
+        let material = self.material.as_ref().or(material);
+
+        f(self, &world, material);
+        for child in &self.children {
+            child.walk(&world, material, f);
+        }
+    }
+}
+
+/// Hierarchy of objects and the cameras that can look at them
+#[derive(Clone, Default)]
+pub struct Scene {
+    /// Nodes placed directly in the world
+    pub nodes: Vec<Node>,
+    /// Cameras in world space
+    pub cameras: Vec<Camera>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Scene::default()
+    }
+
+    pub fn with_node(mut self, node: Node) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// First node with the given name, searching each root depth first
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Node> {
+        self.nodes.iter_mut().find_map(|node| node.find_mut(name))
+    }
+
+    /// Calls `f` on every node, parents before their children, along with the matrix placing
+    /// the node in the world and the material it inherits
+    pub fn walk<'a, F>(&'a self, mut f: F)
+    where
+        F: FnMut(&'a Node, &Matrix4, Option<&'a Material>),
+    {
+        for node in &self.nodes {
+            node.walk(&Matrix4::IDENTITY, None, &mut f);
+        }
+    }
+
+    /// Box around every model in world space, or an empty box at the origin if there are none
+    pub fn aabb(&self) -> Aabb {
+        let mut aabb: Option<Aabb> = None;
+        self.walk(|node, world, _| {
+            if let Some(model) = &node.model {
+                let model_aabb = model.aabb().transform(world);
+                aabb = Some(aabb.map_or(model_aabb, |aabb| aabb.union(&model_aabb)));
+            }
+        });
+
+        aabb.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{model::obj::load_obj, vector::vector3::Vector3};
+
+    #[test]
+    fn test_walk() {
+        let red = Material {
+            color: Color::new(0xff, 0, 0, 0xff),
+            ..Default::default()
+        };
+
+        let mut parent = Transform::new(std::f64::consts::FRAC_PI_2, 0.);
+        parent.position = Vector3::new(0., 0., -5.);
+        let child = Transform {
+            position: Vector3::new(1., 0., 0.),
+            scale: Vector3::new(2., 2., 2.),
+            ..Default::default()
+        };
+
+        let cube = load_obj("models/cube.obj").expect("Failed to read model data");
+        let mut scene = Scene::new().with_node(
+            Node::new(parent)
+                .with_name("parent")
+                .with_material(red.clone())
+                .with_child(Node::new(child).with_name("child").with_model(cube)),
+        );
+
+        let mut visited = Vec::new();
+        scene.walk(|node, world, material| {
+            visited.push((node.name.clone(), *world, material.cloned()));
+        });
+
+        // The child is placed in the space of its parent and inherits its material
+        assert_eq!(visited.len(), 2);
+        let (name, world, material) = &visited[1];
+        assert_eq!((name.as_str(), material), ("child", &Some(red)));
+        let origin = world.transform_point(Vector3::default());
+        assert!((origin - Vector3::new(0., 0., -4.)).length() < 1e-9);
+
+        let aabb = scene.aabb();
+        assert!((aabb.center() - Vector3::new(0., 0., -4.)).length() < 1e-9);
+        assert!((aabb.size() - Vector3::new(4., 4., 4.)).length() < 1e-9);
+
+        scene.find_mut("child").unwrap().transform.scale = Vector3::new(1., 1., 1.);
+        assert!((scene.aabb().size() - Vector3::new(2., 2., 2.)).length() < 1e-9);
+        assert!(scene.find_mut("missing").is_none());
+    }
+}
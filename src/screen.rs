@@ -3,6 +3,7 @@ use std::{
     f64,
     io::{Write, stdout},
     num::NonZero,
+    ops::Range,
 };
 
 use kitty_image::{
@@ -14,22 +15,79 @@ use nix::{
     libc::{self, winsize},
 };
 use rand::Rng;
+use rayon::prelude::*;
+
+mod canvas;
 
 use crate::{
-    model::Model,
-    vector::{transform::Transform, vector2::Vector2, vector3::Vector3},
+    buffer::Buffer,
+    camera::{Camera, Projection},
+    model::{Model, bounds::BoundingSphere},
+    raster,
+    scene::{Material, Scene},
+    texture::Texture,
+    vector::{
+        matrix4::Matrix4, transform::Transform, vector2::Vector2, vector3::Vector3,
+        vector4::Vector4,
+    },
 };
 
+/// Area in pixels that a triangle should cover at least, before switching to a less detailed
+/// version of a model
+const LOD_PIXELS_PER_TRIANGLE: f64 = 16.;
+
+/// Height in pixels of the tiles that triangles are binned into
+const TILE_ROWS: usize = 16;
+
+/// Triangles a model needs before its tiles are rasterized on the thread pool
+const PARALLEL_MIN_TRIANGLES: usize = 64;
+
+/// Fraction of their depth by which lines are moved towards the camera, so that edges are not
+/// hidden by the triangles they belong to
+const LINE_DEPTH_BIAS: f64 = 0.01;
+
 pub struct Screen {
     pub width: usize,
     pub height: usize,
     pub size: Vector2<f64>,
-    pub fov: f64,
+    /// Which side of the triangles is skipped
+    pub cull_mode: CullMode,
+    /// Order of the corners of front facing triangles, as seen from the camera
+    pub winding: Winding,
+    /// Rasterizes the tiles of large models in parallel. The image is the same either way
+    pub multithreaded: bool,
+    /// How alpha blended triangles are combined with each other
+    pub transparency: Transparency,
+    /// Whether the triangles are filled, or drawn as lines or points
+    pub polygon_mode: PolygonMode,
+    /// Color and width of the lines and points of the polygon modes
+    pub line_style: LineStyle,
 
     scale: usize,
-    frame_buf: Vec<Vec<Color>>,
-    depth_buf: Vec<Vec<f64>>,
+    anti_aliasing: AntiAliasing,
+    frame_buf: Buffer<Color>,
+    /// Colors of the samples that are filtered into the image when anti-aliasing
+    sample_buf: Buffer<Color>,
+    /// View space depth of the closest surface drawn on each sample
+    depth_buf: Buffer<f32>,
+    /// Transparent fragments drawn on each sample, with weighted blended transparency
+    accumulation_buf: Buffer<Accumulation>,
+    /// Whether any fragment was accumulated since the last composite
+    accumulated: bool,
+    /// Whether the image shows everything rendered since the last clear
+    resolved: bool,
+    /// Set while filling the depth buffer with the whole scene, before drawing hidden lines
+    depth_prepass: bool,
     frame: u32,
+    /// Screen space position of each vertex of the model being rendered
+    vertex_cache: Vec<Vector3<f64>>,
+    /// View space position of each vertex of the model being rendered
+    view_cache: Vec<Vector3<f64>>,
+    /// Triangles of the model being rendered that survived culling
+    setups: Vec<TriangleSetup>,
+    /// Indices of the setups overlapping each tile
+    bins: Vec<Vec<usize>>,
+    stats: RenderStats,
 
     action: Action,
 }
@@ -57,10 +115,26 @@ impl Screen {
             height,
             size: Vector2::new(width as f64, height as f64),
             scale: 1,
-            frame_buf: vec![vec![Color::default(); width]; height],
-            depth_buf: vec![vec![f64::NEG_INFINITY; width]; height],
+            anti_aliasing: AntiAliasing::None,
+            frame_buf: Buffer::new(width, height, Color::default()),
+            sample_buf: Buffer::new(0, 0, Color::default()),
+            depth_buf: Buffer::new(width, height, f32::NEG_INFINITY),
+            accumulation_buf: Buffer::new(0, 0, Accumulation::default()),
+            accumulated: false,
+            resolved: true,
+            depth_prepass: false,
             frame: 1,
-            fov: 45.,
+            vertex_cache: Vec::new(),
+            view_cache: Vec::new(),
+            setups: Vec::new(),
+            bins: Vec::new(),
+            stats: RenderStats::default(),
+            cull_mode: CullMode::Back,
+            winding: Winding::CounterClockwise,
+            multithreaded: true,
+            transparency: Transparency::Sorted,
+            polygon_mode: PolygonMode::Fill,
+            line_style: LineStyle::default(),
             action,
         }
     }
@@ -77,6 +151,8 @@ impl Screen {
     where
         W: Write,
     {
+        self.resolve();
+
         let overflow = self.frame.checked_add(1);
         match overflow {
             Some(new) => self.frame = new,
@@ -87,18 +163,60 @@ impl Screen {
         let mut command = Command::new(self.action);
         command.id = Some(ID(NonZero::new(self.frame as u32).unwrap()));
         command.quietness = Quietness::SuppressAll;
-        command.payload = buf_to_payload(&self.frame_buf);
+        command.payload = Cow::Borrowed(self.frame_buf.as_bytes());
 
         // Wrap the command in escape codes
         let command = WrappedCommand::new(command);
         command.send_chunked(writer).unwrap();
         writer.flush().unwrap();
 
-        self.clear_frame_buf();
-        self.clear_depth_buf();
+        self.clear();
         self.clear_frames();
     }
 
+    /// Clears the image, the depth buffer and the stats, without sending anything to the
+    /// terminal
+    pub fn clear(&mut self) {
+        self.frame_buf.fill(Color::default());
+        self.sample_buf.fill(Color::default());
+        self.depth_buf.fill(f32::NEG_INFINITY);
+        if self.accumulated {
+            self.accumulation_buf.fill(Accumulation::default());
+            self.accumulated = false;
+        }
+        self.resolved = true;
+        self.stats = RenderStats::default();
+    }
+
+    /// The image as it is sent to the terminal, each pixel repeated `scale` times in both
+    /// directions. Rendered models only show in it once the frame is resolved, see
+    /// [`Screen::resolve`]
+    pub fn frame_buf(&self) -> &Buffer<Color> {
+        &self.frame_buf
+    }
+
+    /// View space depth of the closest surface on each sample, negative in front of the camera
+    /// and negative infinity where nothing was drawn. Without anti-aliasing there is one sample
+    /// per pixel
+    pub fn depth_buf(&self) -> &Buffer<f32> {
+        &self.depth_buf
+    }
+
+    /// Color of a pixel, before scaling, once the frame is resolved. `None` outside of the
+    /// screen
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.frame_buf.get(x * self.scale, y * self.scale).copied()
+    }
+
+    /// Work done and skipped since the last clear
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
     /// Renders to stdout
     pub fn draw(&mut self) {
         self.draw_to(&mut stdout());
@@ -118,139 +236,762 @@ impl Screen {
         }
 
         // Scale image buffer
-        self.frame_buf = vec![vec![Color::default(); scaled_width]; scaled_height];
+        self.frame_buf = Buffer::new(scaled_width, scaled_height, Color::default());
+    }
+
+    /// Smooths the edges of the triangles by rendering more than one sample per pixel
+    pub fn anti_aliasing(&mut self, anti_aliasing: AntiAliasing) {
+        match anti_aliasing {
+            AntiAliasing::None => {}
+            AntiAliasing::Supersampling { factor, .. } => {
+                assert!(factor > 0, "Supersampling factor must be at least 1")
+            }
+            AntiAliasing::Multisampling { samples } => assert!(
+                raster::sample_offsets(samples).is_some(),
+                "Unsupported number of samples: {samples}"
+            ),
+        }
+        self.anti_aliasing = anti_aliasing;
+
+        // Samples of the same pixel are next to each other in a row
+        let (width, height) = match anti_aliasing {
+            AntiAliasing::None => (self.width, self.height),
+            AntiAliasing::Supersampling { factor, .. } => {
+                (self.width * factor, self.height * factor)
+            }
+            AntiAliasing::Multisampling { samples } => (self.width * samples, self.height),
+        };
+
+        self.sample_buf = match anti_aliasing {
+            AntiAliasing::None => Buffer::new(0, 0, Color::default()),
+            _ => Buffer::new(width, height, Color::default()),
+        };
+        self.depth_buf = Buffer::new(width, height, f32::NEG_INFINITY);
+        self.accumulation_buf = Buffer::new(0, 0, Accumulation::default());
+        self.accumulated = false;
+        self.frame_buf.fill(Color::default());
+        self.resolved = true;
+    }
+
+    /// Draws a model placed in the world by `transform`, as seen from `camera`
+    pub fn render(&mut self, camera: &Camera, model: &Model, transform: &Transform) {
+        self.render_model(camera, model, &transform.to_matrix(), None);
+    }
+
+    /// Draws every model of the scene, placing each node in the space of its parent. Models
+    /// with a blended material are drawn after the opaque ones, from the farthest to the closest
+    pub fn render_scene(&mut self, camera: &Camera, scene: &Scene) {
+        // Hidden lines need the depth of the whole scene before drawing any edge
+        if self.polygon_mode == PolygonMode::HiddenLine {
+            let stats = self.stats;
+            self.depth_prepass = true;
+            scene.walk(|node, world, material| {
+                if let Some(model) = &node.model {
+                    self.render_model(camera, model, world, material);
+                }
+            });
+            self.depth_prepass = false;
+            self.stats = stats;
+        }
+
+        let mut blended = Vec::new();
+        scene.walk(|node, world, material| {
+            let Some(model) = &node.model else {
+                return;
+            };
+
+            match material {
+                Some(material) if material.blend != BlendMode::Opaque => {
+                    let center = model.bounding_sphere().transform(world).center;
+                    blended.push((camera.world_to_view(center).z, model, *world, material));
+                }
+                _ => self.render_model(camera, model, world, material),
+            }
+        });
+
+        blended.sort_by(|a, b| a.0.total_cmp(&b.0));
+        for (_, model, world, material) in blended {
+            self.render_model(camera, model, &world, Some(material));
+        }
     }
 
-    pub fn render(&mut self, model: &Model, transform: &Transform) {
-        for (color_idx, triangle) in model.points.windows(3).step_by(3).enumerate() {
-            let triangle = (
-                self.vertex_to_screen(triangle[0], transform, self.fov),
-                self.vertex_to_screen(triangle[1], transform, self.fov),
-                self.vertex_to_screen(triangle[2], transform, self.fov),
+    /// Draws the same model once for each transform, tinting each copy with the color at the
+    /// same index in `colors`. Without colors the model keeps its own. The bounding sphere of
+    /// the model is only computed once
+    pub fn render_instanced(
+        &mut self,
+        camera: &Camera,
+        model: &Model,
+        transforms: &[Transform],
+        colors: &[Color],
+    ) {
+        assert!(
+            colors.is_empty() || colors.len() == transforms.len(),
+            "Instance colors do not match the transform count"
+        );
+
+        let sphere = model.bounding_sphere();
+
+        for (idx, transform) in transforms.iter().enumerate() {
+            let world = transform.to_matrix();
+            let material = colors.get(idx).map(|&color| Material {
+                color,
+                ..Default::default()
+            });
+            self.draw_model(
+                camera,
+                model,
+                &world,
+                &sphere.transform(&world),
+                material.as_ref(),
             );
+        }
+    }
+
+    /// Draws a model placed in the world by the `world` matrix, tinted by the material
+    fn render_model(
+        &mut self,
+        camera: &Camera,
+        model: &Model,
+        world: &Matrix4,
+        material: Option<&Material>,
+    ) {
+        let sphere = model.bounding_sphere().transform(world);
+        self.draw_model(camera, model, world, &sphere, material);
+    }
+
+    /// Rasterizes a model, `sphere` bounds it in world space. Models outside of the view are
+    /// skipped before transforming any vertex
+    fn draw_model(
+        &mut self,
+        camera: &Camera,
+        model: &Model,
+        world: &Matrix4,
+        sphere: &BoundingSphere,
+        material: Option<&Material>,
+    ) {
+        let aspect = camera.aspect_ratio(&self.size);
+        if !camera.frustum(aspect).intersects_sphere(sphere) {
+            self.stats.models_culled += 1;
+            return;
+        }
+        self.resolved = false;
+        self.stats.models_drawn += 1;
+
+        let model = self.level_of_detail(camera, model, sphere);
+
+        // The material texture only applies to models with texture coordinates
+        let texture = material
+            .and_then(|material| material.texture.as_ref())
+            .filter(|_| !model.uvs.is_empty())
+            .or(model.texture.as_ref());
+
+        // Transform each vertex once, triangles sharing it reuse the result
+        let (width, height) = self.raster_size();
+        let size = Vector2::new(width as f64, height as f64);
+        let model_view = camera.view_matrix() * *world;
+        let projection = camera.projection_matrix(aspect);
+        let mut view_cache = std::mem::take(&mut self.view_cache);
+        view_cache.clear();
+        view_cache.extend(
+            model
+                .vertices
+                .iter()
+                .map(|&vertex| model_view.transform_point(vertex)),
+        );
+        let mut vertex_cache = std::mem::take(&mut self.vertex_cache);
+        vertex_cache.clear();
+        vertex_cache.extend(
+            view_cache
+                .iter()
+                .map(|&view_vertex| view_to_screen(view_vertex, &projection, size)),
+        );
+
+        let perspective = matches!(camera.projection, Projection::Perspective { .. });
+        let mut setups = std::mem::take(&mut self.setups);
+        setups.clear();
 
+        let (cull_mode, winding, anti_aliasing) =
+            (self.cull_mode, self.winding, self.anti_aliasing);
+        let setup = |corners: [Vector3<f64>; 3], weights, vertices, color| {
             // Min and max bounds for a triangle
             let min_bounds = Vector2::new(
-                triangle.0.x.min(triangle.1.x).min(triangle.2.x),
-                triangle.0.y.min(triangle.1.y).min(triangle.2.y),
+                corners[0].x.min(corners[1].x).min(corners[2].x),
+                corners[0].y.min(corners[1].y).min(corners[2].y),
             );
             let max_bounds = Vector2::new(
-                triangle.0.x.max(triangle.1.x).max(triangle.2.x),
-                triangle.0.y.max(triangle.1.y).max(triangle.2.y),
+                corners[0].x.max(corners[1].x).max(corners[2].x),
+                corners[0].y.max(corners[1].y).max(corners[2].y),
             );
 
-            // Get the bounding box of the triangle
-            let block_start = (
-                (min_bounds.x as usize).clamp(0, self.width - 1),
-                (min_bounds.y as usize).clamp(0, self.height - 1),
-            );
-            let block_end = (
-                (max_bounds.x.ceil() as usize).clamp(0, self.width - 1),
-                (max_bounds.y.ceil() as usize).clamp(0, self.height - 1),
-            );
+            // Fully beside the screen
+            if max_bounds.x < 0.
+                || max_bounds.y < 0.
+                || min_bounds.x >= size.x
+                || min_bounds.y >= size.y
+            {
+                return Err(Skipped::Outside);
+            }
 
-            // Precalculate the steps to rasterize the triangle (Optimization)
-            let top_left_point = Vector2::new(block_start.0 as f64, block_start.1 as f64);
-            let delta_weights_row = Vector3::new(
-                triangle.1.x - triangle.0.x,
-                triangle.2.x - triangle.1.x,
-                triangle.0.x - triangle.2.x,
-            );
+            // Corners too far outside of the screen for the fixed point coordinates
+            let raster_triangle =
+                raster::Triangle::new([corners[0].into(), corners[1].into(), corners[2].into()])
+                    .ok_or(Skipped::Degenerate)?;
 
-            let delta_weights_col = Vector3::new(
-                triangle.0.y - triangle.1.y,
-                triangle.1.y - triangle.2.y,
-                triangle.2.y - triangle.0.y,
-            );
+            let signed_area = raster_triangle.signed_area();
+            if signed_area == 0 {
+                return Err(Skipped::Degenerate);
+            }
 
-            let mut weights = top_left_point.get_barycentric_weights(
-                &triangle.0.into(),
-                &triangle.1.into(),
-                &triangle.2.into(),
-            );
+            // Pixel rows go down, which mirrors the screen: triangles that are counter clockwise
+            // as seen from the camera have a negative area in pixel coordinates
+            let counter_clockwise = signed_area < 0;
+            let front = counter_clockwise == (winding == Winding::CounterClockwise);
+            if cull_mode.culls(front) {
+                return Err(Skipped::Backface);
+            }
 
-            let triangle_area = Vector2::signed_parallelogram_area(
-                &triangle.0.into(),
-                &triangle.1.into(),
-                &triangle.2.into(),
-            );
+            // Attributes are interpolated linearly in view space, which for a perspective
+            // projection means weighting each vertex by its inverse depth
+            let depths = Vector3::new(corners[0].z, corners[1].z, corners[2].z);
+            let inverse_w = match perspective {
+                true => 1. / depths,
+                false => Vector3::new(1., 1., 1.),
+            };
+
+            // Samples can be covered up to half a pixel past the centers
+            let rows = raster_triangle.bounds().1;
+            let margin = match anti_aliasing {
+                AntiAliasing::Multisampling { .. } => 1,
+                _ => 0,
+            };
+            Ok(TriangleSetup {
+                rows: (rows.start - margin).max(0) as usize
+                    ..(rows.end + margin).clamp(0, height as i64) as usize,
+                raster: raster_triangle,
+                vertices,
+                corners: weights,
+                color,
+                depths,
+                inverse_w,
+            })
+        };
 
-            if triangle_area == 0. {
+        let identity = [
+            Vector3::new(1., 0., 0.),
+            Vector3::new(0., 1., 0.),
+            Vector3::new(0., 0., 1.),
+        ];
+        for (color_idx, &[a, b, c]) in model.triangles.iter().enumerate() {
+            let view = [view_cache[a], view_cache[b], view_cache[c]];
+            let max_depth = view[0].z.max(view[1].z).max(view[2].z);
+            let min_depth = view[0].z.min(view[1].z).min(view[2].z);
+
+            // Fully in front of the near plane or past the far one
+            if min_depth > -camera.near || max_depth < -camera.far {
+                self.stats.triangles_outside += 1;
                 continue;
             }
 
-            // Render only the pixels contained in the triangle
-            for y in block_start.1..block_end.1 {
-                let mut step = weights;
-                for x in block_start.0..block_end.0 {
-                    if !Vector2::is_in_triangle(&step) {
-                        step += delta_weights_col;
-                        continue;
+            let color = match material {
+                Some(material) => model.face_colors[color_idx].multiply(material.color),
+                None => model.face_colors[color_idx],
+            };
+
+            // Corners in front of the near plane can't be projected, so the triangle is cut
+            // along it into 1 or 2 triangles
+            let mut pieces = [([Vector3::default(); 3], identity); 2];
+            let count = if max_depth <= -camera.near {
+                pieces[0] = (
+                    [vertex_cache[a], vertex_cache[b], vertex_cache[c]],
+                    identity,
+                );
+                1
+            } else {
+                let (polygon, len) = clip_near(view, camera.near);
+                for (idx, piece) in pieces.iter_mut().enumerate().take(len - 2) {
+                    let corners = [polygon[0], polygon[idx + 1], polygon[idx + 2]];
+                    *piece = (
+                        corners.map(|(view, _)| view_to_screen(view, &projection, size)),
+                        corners.map(|(_, weights)| weights),
+                    );
+                }
+                len - 2
+            };
+
+            // Counted once, as drawn if any of its pieces is
+            let mut skipped = None;
+            let mut drawn = false;
+            for &(corners, weights) in &pieces[..count] {
+                match setup(corners, weights, [a, b, c], color) {
+                    Ok(triangle) => {
+                        setups.push(triangle);
+                        drawn = true;
+                    }
+                    Err(reason) => {
+                        skipped.get_or_insert(reason);
                     }
+                }
+            }
+            match (drawn, skipped) {
+                (true, _) | (false, None) => self.stats.triangles_drawn += 1,
+                (false, Some(Skipped::Outside)) => self.stats.triangles_outside += 1,
+                (false, Some(Skipped::Backface)) => self.stats.triangles_backface += 1,
+                (false, Some(Skipped::Degenerate)) => self.stats.triangles_skipped += 1,
+            }
+        }
 
-                    // Calculate the barycentric coordinates
-                    let barycentric_coords = Vector3::new(
-                        step.x / triangle_area,
-                        step.y / triangle_area,
-                        step.z / triangle_area,
-                    );
+        // Blended triangles are drawn from the farthest to the closest, unless their order does
+        // not matter
+        let blend = material.map_or(BlendMode::Opaque, |material| material.blend);
+        if blend == BlendMode::Alpha && self.transparency == Transparency::Sorted {
+            let depth = |setup: &TriangleSetup| setup.depths.x + setup.depths.y + setup.depths.z;
+            setups.sort_by(|a, b| depth(a).total_cmp(&depth(b)));
+        }
 
-                    let interpolated_depth = 1. / (barycentric_coords * (1. / Vector3::new(triangle.0.z, triangle.1.z, triangle.2.z)));
+        self.vertex_cache = vertex_cache;
+        self.view_cache = view_cache;
+
+        let texture = texture.map(|texture| &**texture);
+        match self.polygon_mode {
+            PolygonMode::Fill => self.rasterize(camera, model, texture, material, &setups, false),
+            PolygonMode::Wireframe => self.draw_edges(camera, &setups, false),
+            PolygonMode::Points => self.draw_points(camera, &setups),
+            PolygonMode::FillAndWireframe => {
+                self.rasterize(camera, model, texture, material, &setups, false);
+                self.draw_edges(camera, &setups, true);
+            }
+            PolygonMode::HiddenLine => {
+                self.rasterize(camera, model, texture, material, &setups, true);
+                if !self.depth_prepass {
+                    self.draw_edges(camera, &setups, true);
+                }
+            }
+        }
 
-                    if interpolated_depth < self.depth_buf[y][x] {
-                        step += delta_weights_col;
-                        continue;
-                    };
+        self.setups = setups;
+    }
+
+    /// Bins the triangles into tiles and fills each tile with the triangles overlapping it, in
+    /// the order they were given. Tiles are bands of rows spanning the whole width, so each one
+    /// owns its part of the buffers and they can be filled in parallel. With `depth_only` only
+    /// the depth buffer is written
+    fn rasterize(
+        &mut self,
+        camera: &Camera,
+        model: &Model,
+        texture: Option<&Texture>,
+        material: Option<&Material>,
+        setups: &[TriangleSetup],
+        depth_only: bool,
+    ) {
+        let (width, height) = self.raster_size();
+        let tile_count = height.div_ceil(TILE_ROWS);
+        let mut bins = std::mem::take(&mut self.bins);
+        bins.resize_with(tile_count, Vec::new);
+        bins.iter_mut().for_each(Vec::clear);
+
+        for (idx, setup) in setups.iter().enumerate() {
+            if setup.rows.is_empty() {
+                continue;
+            }
 
-                    self.depth_buf[y][x] = interpolated_depth;
+            for bin in &mut bins[setup.rows.start / TILE_ROWS..=(setup.rows.end - 1) / TILE_ROWS] {
+                bin.push(idx);
+            }
+        }
 
-                    render_scaled((x, y), self.scale, |scaled_x, scaled_y| {
-                        self.frame_buf[scaled_y][scaled_x] = model.face_colors[color_idx];
+        let blend = material.map_or(BlendMode::Opaque, |material| material.blend);
+        let pass = Pass {
+            camera,
+            model,
+            texture,
+            setups,
+            bins: &bins,
+            width,
+            depth_write: depth_only || material.is_none_or(|material| material.depth_write),
+            // Handing out small models to the thread pool costs more than it saves
+            parallel: self.multithreaded && setups.len() >= PARALLEL_MIN_TRIANGLES,
+        };
+
+        if blend == BlendMode::Alpha
+            && self.transparency == Transparency::WeightedBlended
+            && !depth_only
+        {
+            // Accumulated on each sample, and blended over the image when the frame is resolved
+            if self.accumulation_buf.as_slice().len() != self.depth_buf.as_slice().len() {
+                self.accumulation_buf = Buffer::new(
+                    self.depth_buf.width,
+                    self.depth_buf.height,
+                    Accumulation::default(),
+                );
+            }
+            self.accumulated = true;
+
+            let depth = self.depth_buf.as_mut_slice();
+            let accumulations = self.accumulation_buf.as_mut_slice();
+            let write = |accumulation: &mut Accumulation, color, depth| {
+                accumulation.add(color, depth);
+            };
+            match self.anti_aliasing {
+                AntiAliasing::Multisampling { samples } => {
+                    pass.fill_samples(samples, depth, accumulations, write)
+                }
+                _ => pass.fill_pixels(depth, (accumulations, width, 1), write),
+            }
+        } else {
+            let depth = self.depth_buf.as_mut_slice();
+            let write = |target: &mut Color, color: Color, _| {
+                if !depth_only {
+                    *target = color.blend(*target, blend);
+                }
+            };
+            match self.anti_aliasing {
+                AntiAliasing::None => {
+                    let frame_width = self.frame_buf.width;
+                    let frame = self.frame_buf.as_mut_slice();
+                    pass.fill_pixels(depth, (frame, frame_width, self.scale), write)
+                }
+                AntiAliasing::Supersampling { .. } => {
+                    pass.fill_pixels(depth, (self.sample_buf.as_mut_slice(), width, 1), write)
+                }
+                AntiAliasing::Multisampling { samples } => {
+                    pass.fill_samples(samples, depth, self.sample_buf.as_mut_slice(), write)
+                }
+            }
+        }
+
+        self.bins = bins;
+    }
+
+    /// Blends the accumulated transparent fragments over the samples they were drawn on, and
+    /// clears them
+    fn composite(&mut self) {
+        self.accumulated = false;
+
+        let accumulations = self.accumulation_buf.as_mut_slice();
+        match self.anti_aliasing {
+            AntiAliasing::None => {
+                let scale = self.scale;
+                let frame_width = self.frame_buf.width;
+                let frame = self.frame_buf.as_mut_slice();
+                for (idx, accumulation) in accumulations.iter_mut().enumerate() {
+                    let (x, y) = (idx % self.width, idx / self.width);
+                    render_scaled((x, y), scale, |scaled_x, scaled_y| {
+                        let target = &mut frame[scaled_y * frame_width + scaled_x];
+                        *target = accumulation.composite(*target);
                     });
+                    *accumulation = Accumulation::default();
+                }
+            }
+            _ => {
+                let samples = self.sample_buf.as_mut_slice();
+                for (target, accumulation) in samples.iter_mut().zip(accumulations) {
+                    *target = accumulation.composite(*target);
+                    *accumulation = Accumulation::default();
+                }
+            }
+        }
+    }
 
-                    step += delta_weights_col;
+    /// Draws the edges of the triangles once each, with the line style. With `depth_test` the
+    /// parts hidden behind what is already drawn are skipped
+    fn draw_edges(&mut self, camera: &Camera, setups: &[TriangleSetup], depth_test: bool) {
+        let mut edges: Vec<[usize; 2]> = setups
+            .iter()
+            .flat_map(|setup| {
+                let [a, b, c] = setup.vertices;
+                [[a, b], [b, c], [c, a]].map(|[from, to]| [from.min(to), from.max(to)])
+            })
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let (width, height) = self.raster_size();
+        let size = Vector2::new(width as f64, height as f64);
+        let projection = camera.projection_matrix(camera.aspect_ratio(&self.size));
+        let perspective = matches!(camera.projection, Projection::Perspective { .. });
+
+        // Edges are cut where they cross the near plane, like the triangles
+        let near = -camera.near;
+        let lines: Vec<_> = edges
+            .into_iter()
+            .filter_map(|[a, b]| {
+                let (from, to) = (self.view_cache[a], self.view_cache[b]);
+                match (from.z <= near, to.z <= near) {
+                    (true, true) => Some((self.vertex_cache[a], self.vertex_cache[b])),
+                    (false, false) => None,
+                    (from_inside, _) => {
+                        let cut = from.lerp(&to, (near - from.z) / (to.z - from.z));
+                        let cut = view_to_screen(cut, &projection, size);
+                        match from_inside {
+                            true => Some((self.vertex_cache[a], cut)),
+                            false => Some((cut, self.vertex_cache[b])),
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        let line_width = self.line_style.width * self.raster_scale();
+        let color = self.line_style.color;
+
+        for (from, to) in lines {
+            raster::line(
+                from.into(),
+                to.into(),
+                line_width,
+                0..width,
+                0..height,
+                |x, y, coverage, along| {
+                    // The inverse of the depth is linear on the screen for perspective cameras
+                    let depth = match perspective {
+                        true => 1. / ((1. - along) / from.z + along / to.z),
+                        false => from.z + (to.z - from.z) * along,
+                    };
+                    self.plot(x, y, color, coverage, depth_test.then_some(depth));
+                },
+            );
+        }
+    }
+
+    /// Draws the corners of the triangles in the view once each, as dots as wide as the lines
+    fn draw_points(&mut self, camera: &Camera, setups: &[TriangleSetup]) {
+        let mut vertices: Vec<usize> = setups
+            .iter()
+            .flat_map(|setup| setup.vertices)
+            .filter(|&vertex| self.view_cache[vertex].z <= -camera.near)
+            .collect();
+        vertices.sort_unstable();
+        vertices.dedup();
+
+        let (width, height) = self.raster_size();
+        let size = self.line_style.width * self.raster_scale();
+        let color = self.line_style.color;
+
+        for vertex in vertices {
+            let point = self.vertex_cache[vertex].into();
+            raster::line(
+                point,
+                point,
+                size,
+                0..width,
+                0..height,
+                |x, y, coverage, _| {
+                    self.plot(x, y, color, coverage, None);
+                },
+            );
+        }
+    }
+
+    /// Blends `color` over a pixel of the raster grid, by its alpha scaled by `coverage`. With
+    /// a `depth`, samples with something closer drawn on them are skipped
+    fn plot(&mut self, x: usize, y: usize, color: Color, coverage: f64, depth: Option<f64>) {
+        let color = color.faded(coverage);
+
+        // Lines lying on a surface are slightly in front of it
+        let visible = |closest: f32| {
+            depth.is_none_or(|depth| depth + depth.abs() * LINE_DEPTH_BIAS >= closest as f64)
+        };
+
+        match self.anti_aliasing {
+            AntiAliasing::None => {
+                if !visible(self.depth_buf[(x, y)]) {
+                    return;
+                }
+
+                let frame = &mut self.frame_buf;
+                render_scaled((x, y), self.scale, |scaled_x, scaled_y| {
+                    let target = &mut frame[(scaled_x, scaled_y)];
+                    *target = color.blend(*target, BlendMode::Alpha);
+                });
+            }
+            AntiAliasing::Supersampling { .. } => {
+                if visible(self.depth_buf[(x, y)]) {
+                    let target = &mut self.sample_buf[(x, y)];
+                    *target = color.blend(*target, BlendMode::Alpha);
+                }
+            }
+            AntiAliasing::Multisampling { samples } => {
+                for sample in 0..samples {
+                    let sample_x = x * samples + sample;
+                    if visible(self.depth_buf[(sample_x, y)]) {
+                        let target = &mut self.sample_buf[(sample_x, y)];
+                        *target = color.blend(*target, BlendMode::Alpha);
+                    }
                 }
-                weights += delta_weights_row;
             }
         }
     }
 
-    /// Transform vertex position to screen space (pixel coordinates)
-    fn vertex_to_screen(
-        &self,
-        vertex: Vector3<f64>,
-        transform: &Transform,
-        fov: f64,
-    ) -> Vector3<f64> {
-        let world_vertex = transform.vertex_to_world(vertex);
-
-        let screen_height_world = f64::tan(fov / 2.) * 2.0;
-        let pixels_per_world_unit = self.size.y / screen_height_world / world_vertex.z;
-
-        let pixel_offset = Vector2::new(world_vertex.x, world_vertex.y) * pixels_per_world_unit;
-        return Vector3::new(
-            self.size.x / 2. + pixel_offset.x,
-            self.size.y / 2. + pixel_offset.y,
-            world_vertex.z,
-        );
+    /// Pixels of the raster grid along one pixel of the screen
+    fn raster_scale(&self) -> f64 {
+        match self.anti_aliasing {
+            AntiAliasing::Supersampling { factor, .. } => factor as f64,
+            _ => 1.,
+        }
     }
 
-    fn clear_frame_buf(&mut self) {
-        for y in self.frame_buf.iter_mut() {
-            for x in y {
-                *x = Color::default()
+    /// Size of the grid of pixels that triangles are rasterized on
+    fn raster_size(&self) -> (usize, usize) {
+        match self.anti_aliasing {
+            AntiAliasing::Supersampling { factor, .. } => {
+                (self.width * factor, self.height * factor)
             }
+            _ => (self.width, self.height),
         }
     }
 
-    fn clear_depth_buf(&mut self) {
-        for y in self.depth_buf.iter_mut() {
-            for x in y {
-                *x = f64::NEG_INFINITY;
+    /// Finishes the frame: blends the transparent fragments accumulated since the last call
+    /// over the others, then filters the samples down into the image when anti-aliasing. It
+    /// only does anything after rendering, and is called before the frame is sent to the
+    /// terminal and before drawing in 2D. Call it to read the image after rendering
+    pub fn resolve(&mut self) {
+        if self.resolved {
+            return;
+        }
+        self.resolved = true;
+
+        if self.accumulated {
+            self.composite();
+        }
+
+        let (width, height) = (self.width, self.height);
+        let scale = self.scale;
+        let frame_width = self.frame_buf.width;
+        let frame = self.frame_buf.as_mut_slice();
+        let samples = self.sample_buf.as_slice();
+        let mut write = |x: usize, y: usize, color: [f32; 4]| {
+            let color = color.map(|channel| channel.round() as u8);
+            let color = Color::new(color[0], color[1], color[2], color[3]);
+            render_scaled((x, y), scale, |scaled_x, scaled_y| {
+                frame[scaled_y * frame_width + scaled_x] = color;
+            });
+        };
+
+        match self.anti_aliasing {
+            AntiAliasing::None => {}
+
+            AntiAliasing::Supersampling { factor, filter } => {
+                let (offset, weights) = filter.weights(factor);
+                let sample_width = width * factor;
+                let sample_height = height * factor;
+
+                // Weighted average of the samples along one axis, skipping the ones past the edges
+                let filter_axis =
+                    |pixel: usize, count: usize, sample: &dyn Fn(usize) -> [f32; 4]| {
+                        let first = (pixel * factor) as isize + offset;
+                        let mut sum = [0.; 4];
+                        let mut total = 0.;
+                        for (idx, &weight) in weights.iter().enumerate() {
+                            let Ok(position) = usize::try_from(first + idx as isize) else {
+                                continue;
+                            };
+                            if position >= count {
+                                continue;
+                            }
+
+                            let value = sample(position);
+                            for channel in 0..4 {
+                                sum[channel] += value[channel] * weight;
+                            }
+                            total += weight;
+                        }
+                        sum.map(|channel| channel / total)
+                    };
+
+                // Rows first, then columns
+                let mut rows = vec![[0.; 4]; width * sample_height];
+                for sample_y in 0..sample_height {
+                    for x in 0..width {
+                        rows[sample_y * width + x] = filter_axis(x, sample_width, &|sample_x| {
+                            let color = samples[sample_y * sample_width + sample_x];
+                            [color.red, color.green, color.blue, color.alpha].map(f32::from)
+                        });
+                    }
+                }
+                for y in 0..height {
+                    for x in 0..width {
+                        let color =
+                            filter_axis(y, sample_height, &|sample_y| rows[sample_y * width + x]);
+                        write(x, y, color);
+                    }
+                }
+            }
+
+            AntiAliasing::Multisampling { samples: count } => {
+                for y in 0..height {
+                    for x in 0..width {
+                        let first = (y * width + x) * count;
+                        let mut sum = [0.; 4];
+                        for color in &samples[first..first + count] {
+                            let channels =
+                                [color.red, color.green, color.blue, color.alpha].map(f32::from);
+                            for channel in 0..4 {
+                                sum[channel] += channels[channel];
+                            }
+                        }
+                        write(x, y, sum.map(|channel| channel / count as f32));
+                    }
+                }
             }
         }
     }
 
+    /// Picks the least detailed version of the model that still has enough triangles for the
+    /// area it covers on the screen. `sphere` bounds the model in world space
+    fn level_of_detail<'a>(
+        &self,
+        camera: &Camera,
+        model: &'a Model,
+        sphere: &BoundingSphere,
+    ) -> &'a Model {
+        if model.lods.is_empty() {
+            return model;
+        }
+
+        let center = camera.world_to_view(sphere.center);
+        let sphere_radius = sphere.radius;
+
+        // Close to or behind a perspective camera the model may cover the whole screen
+        let perspective = matches!(camera.projection, Projection::Perspective { .. });
+        if perspective && center.z >= -sphere_radius {
+            return model;
+        }
+
+        // Radius in pixels of the bounding sphere on the screen
+        let radius = sphere_radius * camera.pixels_per_unit(-center.z, self.size.y);
+
+        let budget = (f64::consts::PI * radius * radius / LOD_PIXELS_PER_TRIANGLE) as usize;
+
+        model
+            .lods
+            .iter()
+            .rev()
+            .find(|lod| lod.triangles.len() >= budget)
+            .unwrap_or(model)
+    }
+
+    /// Moves the camera back along its view direction until the world space sphere fills the
+    /// screen. Orthographic cameras also resize their visible area to fit it
+    pub fn frame(&self, sphere: &BoundingSphere, camera: &mut Camera) {
+        // The narrowest side of the image limits how much fits
+        let narrowest = camera.aspect_ratio(&self.size).min(1.);
+
+        let distance = match &mut camera.projection {
+            Projection::Perspective { fov } => {
+                let half_fov_tan = (*fov / 2.).tan() * narrowest;
+                sphere.radius / half_fov_tan.atan().sin()
+            }
+            Projection::Orthographic { extent } => {
+                *extent = 2. * sphere.radius / narrowest;
+                sphere.radius + camera.near
+            }
+        };
+
+        camera.position = sphere.center - camera.forward() * distance;
+    }
+
     pub fn clear_frames(&mut self) {
         let action = Action::Delete(ActionDelete {
             hard: true,
@@ -282,6 +1023,549 @@ impl Screen {
     }
 }
 
+/// Triangle ready to be rasterized, with what its pixels need to be shaded
+struct TriangleSetup {
+    raster: raster::Triangle,
+    /// Rows of the screen it may cover
+    rows: Range<usize>,
+    /// Indices of its vertices in the model
+    vertices: [usize; 3],
+    /// Weights of the model's vertices at each corner. Only differs from the identity where the
+    /// near plane cut the triangle
+    corners: [Vector3<f64>; 3],
+    color: Color,
+    /// View space depth of each corner
+    depths: Vector3<f64>,
+    /// Weight of each corner when interpolating attributes
+    inverse_w: Vector3<f64>,
+}
+
+impl TriangleSetup {
+    /// Perspective correct weights of the corners, from their weights on the screen
+    #[inline]
+    fn vertex_weights(&self, barycentric_coords: Vector3<f64>) -> Vector3<f64> {
+        Vector3::new(
+            barycentric_coords.x * self.inverse_w.x,
+            barycentric_coords.y * self.inverse_w.y,
+            barycentric_coords.z * self.inverse_w.z,
+        ) / (barycentric_coords * self.inverse_w)
+    }
+
+    /// Color of the point with the given weights of the corners
+    #[inline]
+    fn shade(
+        &self,
+        model: &Model,
+        texture: Option<&Texture>,
+        vertex_weights: Vector3<f64>,
+    ) -> Color {
+        let Some(texture) = texture else {
+            return self.color;
+        };
+
+        // Perspective correct interpolation of the texture coordinates
+        let [a, b, c] = self.vertices;
+        let vertex_weights = self.corners[0] * vertex_weights.x
+            + self.corners[1] * vertex_weights.y
+            + self.corners[2] * vertex_weights.z;
+        let uv = model.uvs[a] * vertex_weights.x
+            + model.uvs[b] * vertex_weights.y
+            + model.uvs[c] * vertex_weights.z;
+        self.color.multiply(texture.sample(uv))
+    }
+}
+
+/// What the tiles share while a model is rasterized
+struct Pass<'a> {
+    camera: &'a Camera,
+    model: &'a Model,
+    texture: Option<&'a Texture>,
+    setups: &'a [TriangleSetup],
+    /// Indices of the setups overlapping each tile
+    bins: &'a [Vec<usize>],
+    /// Pixels in a row of the grid that triangles are rasterized on
+    width: usize,
+    depth_write: bool,
+    parallel: bool,
+}
+
+impl Pass<'_> {
+    /// Outside of the near and far planes
+    fn clipped(&self, depth: f64) -> bool {
+        depth > -self.camera.near || depth < -self.camera.far
+    }
+
+    /// Rasterizes with one sample per pixel. `write` combines each visible fragment, given
+    /// with its color and depth, into the target of its pixel. A target row is `target_width`
+    /// long, and each pixel covers `scale` by `scale` targets
+    fn fill_pixels<T, W>(
+        &self,
+        depth: &mut [f32],
+        (targets, target_width, scale): (&mut [T], usize, usize),
+        write: W,
+    ) where
+        T: Send,
+        W: Fn(&mut T, Color, f64) + Sync,
+    {
+        let width = self.width;
+        let fill_tile = |tile: usize, depth_rows: &mut [f32], target_rows: &mut [T]| {
+            let first_row = tile * TILE_ROWS;
+            let rows = first_row..first_row + depth_rows.len() / width;
+
+            for &idx in &self.bins[tile] {
+                let setup = &self.setups[idx];
+                let test = raster::DepthTest {
+                    inverse_w: setup.inverse_w,
+                    depths: setup.depths,
+                    range: -self.camera.far..=-self.camera.near,
+                };
+                setup.raster.scan_depth(
+                    0..width,
+                    rows.clone(),
+                    depth_rows,
+                    &test,
+                    |x, y, vertex_weights, interpolated_depth, depth| {
+                        if self.depth_write {
+                            *depth = interpolated_depth as f32;
+                        }
+
+                        let color = setup.shade(self.model, self.texture, vertex_weights);
+                        render_scaled((x, y - first_row), scale, |scaled_x, scaled_y| {
+                            let target = &mut target_rows[scaled_y * target_width + scaled_x];
+                            write(target, color, interpolated_depth);
+                        });
+                    },
+                );
+            }
+        };
+
+        for_each_tile(
+            self.parallel,
+            (depth, TILE_ROWS * width),
+            (targets, TILE_ROWS * scale * target_width),
+            fill_tile,
+        );
+    }
+
+    /// Rasterizes with `samples` samples per pixel, next to each other in the rows of `depth`
+    /// and `targets`. Coverage and depth are tested on each sample, but each pixel is shaded
+    /// once and handed to `write` for each of its visible samples
+    fn fill_samples<T, W>(&self, samples: usize, depth: &mut [f32], targets: &mut [T], write: W)
+    where
+        T: Send,
+        W: Fn(&mut T, Color, f64) + Sync,
+    {
+        let offsets = raster::sample_offsets(samples).unwrap();
+        let width = self.width;
+        let sample_width = width * samples;
+
+        let fill_tile = |tile: usize, depth_rows: &mut [f32], target_rows: &mut [T]| {
+            let first_row = tile * TILE_ROWS;
+            let rows = first_row..first_row + depth_rows.len() / sample_width;
+
+            for &idx in &self.bins[tile] {
+                let setup = &self.setups[idx];
+                let (step_x, step_y) = setup.raster.gradients();
+
+                let scan = |x: usize, y: usize, covered: u32, center: Vector3<f64>| {
+                    let first_sample = (y - first_row) * sample_width + x * samples;
+
+                    let mut visible = [None; 32];
+                    let mut any_visible = false;
+                    for (sample, &(offset_x, offset_y)) in offsets.iter().enumerate() {
+                        if covered & (1 << sample) == 0 {
+                            continue;
+                        }
+
+                        let barycentric_coords = center
+                            + step_x * (offset_x as f64 / 16.)
+                            + step_y * (offset_y as f64 / 16.);
+                        let interpolated_depth =
+                            setup.vertex_weights(barycentric_coords) * setup.depths;
+                        let depth = &mut depth_rows[first_sample + sample];
+
+                        if self.clipped(interpolated_depth) || (interpolated_depth as f32) < *depth
+                        {
+                            continue;
+                        }
+
+                        if self.depth_write {
+                            *depth = interpolated_depth as f32;
+                        }
+                        visible[sample] = Some(interpolated_depth);
+                        any_visible = true;
+                    }
+
+                    if !any_visible {
+                        return;
+                    }
+
+                    // Shaded once, at the center of the pixel
+                    let color = setup.shade(self.model, self.texture, setup.vertex_weights(center));
+                    for (sample, depth) in visible[..samples].iter().enumerate() {
+                        if let Some(depth) = *depth {
+                            write(&mut target_rows[first_sample + sample], color, depth);
+                        }
+                    }
+                };
+                setup
+                    .raster
+                    .scan_samples(0..width, rows.clone(), offsets, scan);
+            }
+        };
+
+        for_each_tile(
+            self.parallel,
+            (depth, TILE_ROWS * sample_width),
+            (targets, TILE_ROWS * sample_width),
+            fill_tile,
+        );
+    }
+}
+
+/// Splits the depth and target buffers into tiles of the given lengths, and calls `fill_tile` on
+/// each of them along with its index
+fn for_each_tile<T, F>(
+    parallel: bool,
+    (depth, depth_tile): (&mut [f32], usize),
+    (colors, color_tile): (&mut [T], usize),
+    fill_tile: F,
+) where
+    T: Send,
+    F: Fn(usize, &mut [f32], &mut [T]) + Sync,
+{
+    if parallel {
+        depth
+            .par_chunks_mut(depth_tile)
+            .zip(colors.par_chunks_mut(color_tile))
+            .enumerate()
+            .for_each(|(tile, (depth_rows, color_rows))| fill_tile(tile, depth_rows, color_rows));
+    } else {
+        depth
+            .chunks_mut(depth_tile)
+            .zip(colors.chunks_mut(color_tile))
+            .enumerate()
+            .for_each(|(tile, (depth_rows, color_rows))| fill_tile(tile, depth_rows, color_rows));
+    }
+}
+
+/// How the fragments of a triangle are combined with the color behind them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Replaces it
+    #[default]
+    Opaque,
+    /// Covers it by the alpha of the fragment
+    Alpha,
+    /// Adds to it, scaled by the alpha of the fragment, which brightens
+    Additive,
+    /// Multiplies it, blended by the alpha of the fragment, which darkens
+    Multiply,
+}
+
+/// How triangles drawn with [`BlendMode::Alpha`] are combined with each other
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Transparency {
+    /// In the order they are drawn, after sorting them from the farthest to the closest
+    #[default]
+    Sorted,
+    /// Weighted blended order-independent transparency: overlapping fragments are averaged,
+    /// weighing closer ones more, which needs no sorting but only approximates their order.
+    /// The fragments of every model drawn until the frame is resolved are blended together, so
+    /// opaque models have to be drawn first to hide them
+    WeightedBlended,
+}
+
+/// Sum of the transparent fragments drawn on a sample, with weighted blended transparency
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Accumulation {
+    /// Premultiplied colors and alphas, each weighted by how close the fragment is
+    color: [f32; 4],
+    /// How much of what is behind the fragments still shows through
+    revealage: f32,
+}
+
+impl Default for Accumulation {
+    fn default() -> Self {
+        Accumulation {
+            color: [0.; 4],
+            revealage: 1.,
+        }
+    }
+}
+
+impl Accumulation {
+    fn add(&mut self, color: Color, depth: f64) {
+        let alpha = color.alpha as f32 / 255.;
+
+        // Equation 9 of "Weighted Blended Order-Independent Transparency" by McGuire and Bavoil
+        let distance = depth.abs() as f32;
+        let weight = alpha
+            * (10. / (1e-5 + (distance / 5.).powi(2) + (distance / 200.).powi(6))).clamp(1e-2, 3e3);
+
+        let channels = [color.red, color.green, color.blue].map(|channel| channel as f32 / 255.);
+        for (sum, channel) in self.color.iter_mut().zip(channels) {
+            *sum += channel * alpha * weight;
+        }
+        self.color[3] += alpha * weight;
+        self.revealage *= 1. - alpha;
+    }
+
+    /// The average of the fragments covering `background` by their combined alpha
+    fn composite(&self, background: Color) -> Color {
+        if self.color[3] <= 0. {
+            return background;
+        }
+
+        let coverage = 1. - self.revealage;
+        let channel = |sum: f32, back: u8| {
+            (sum / self.color[3] * 255. * coverage + back as f32 * self.revealage).round() as u8
+        };
+
+        Color::new(
+            channel(self.color[0], background.red),
+            channel(self.color[1], background.green),
+            channel(self.color[2], background.blue),
+            (255. * coverage + background.alpha as f32 * self.revealage).round() as u8,
+        )
+    }
+}
+
+/// How the triangles of the models are drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PolygonMode {
+    #[default]
+    Fill,
+    /// Every edge, including the ones behind other triangles
+    Wireframe,
+    /// Every corner
+    Points,
+    /// The edges that are not hidden behind the triangles of the scene
+    HiddenLine,
+    /// The filled triangles, with their visible edges on top
+    FillAndWireframe,
+}
+
+/// Look of the lines and points drawn by the polygon modes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineStyle {
+    pub color: Color,
+    /// In pixels, also the size of the points
+    pub width: f64,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        LineStyle {
+            color: Color::new(0xff, 0xff, 0xff, 0xff),
+            width: 1.,
+        }
+    }
+}
+
+/// Look of the text drawn on the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub color: Color,
+    /// Size of the pixels of the glyphs, in pixels of the screen
+    pub scale: usize,
+    /// Point of the text placed at the position it is drawn at
+    pub anchor: Anchor,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            color: Color::new(0xff, 0xff, 0xff, 0xff),
+            scale: 1,
+            anchor: Anchor::default(),
+        }
+    }
+}
+
+/// Point of the box around a text. Each line is also aligned to its side
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Halves of the width and of the height of the box that are before the point
+    fn halves(self) -> (usize, usize) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        }
+    }
+}
+
+/// How the edges of the triangles are smoothed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AntiAliasing {
+    #[default]
+    None,
+    /// Renders `factor` times more pixels in both directions and filters them down to the
+    /// screen size, shading every sample
+    Supersampling { factor: usize, filter: Filter },
+    /// Tests coverage and depth on 2, 4 or 8 samples of each pixel, but shades each pixel once
+    Multisampling { samples: usize },
+}
+
+/// Weights of the samples averaged into a pixel when supersampling
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Filter {
+    /// Same weight for the samples of the pixel
+    #[default]
+    Box,
+    /// Weights falling off linearly up to the centers of the neighbouring pixels, which blurs
+    /// slightly but hides more of the stairs
+    Tent,
+}
+
+impl Filter {
+    /// Weights of consecutive samples along one axis, starting `offset` samples after the first
+    /// one of the pixel
+    fn weights(self, factor: usize) -> (isize, Vec<f32>) {
+        match self {
+            Filter::Box => (0, vec![1.; factor]),
+            Filter::Tent => {
+                let weights = (0..3 * factor)
+                    .map(|idx| {
+                        // Distance in pixels from the sample to the center of the pixel
+                        let distance = (idx as f32 + 0.5) / factor as f32 - 1.5;
+                        (1. - distance.abs()).max(0.)
+                    })
+                    .collect();
+                (-(factor as isize), weights)
+            }
+        }
+    }
+}
+
+/// Counts of the models and triangles drawn and of those skipped, by reason
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    /// Models, or instances of a model, that were rasterized
+    pub models_drawn: usize,
+    /// Models whose bounding sphere is outside of the view
+    pub models_culled: usize,
+    /// Triangles that were rasterized
+    pub triangles_drawn: usize,
+    /// Triangles beside the screen, or outside of the near and far planes
+    pub triangles_outside: usize,
+    /// Triangles removed by the cull mode
+    pub triangles_backface: usize,
+    /// Triangles without area
+    pub triangles_skipped: usize,
+}
+
+/// Faces of the triangles that are not drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CullMode {
+    /// Draws both faces, for open or inconsistently wound meshes
+    None,
+    #[default]
+    Back,
+    Front,
+}
+
+impl CullMode {
+    /// Whether a triangle showing its front (or back) face is skipped
+    pub fn culls(self, front: bool) -> bool {
+        match self {
+            CullMode::None => false,
+            CullMode::Back => !front,
+            CullMode::Front => front,
+        }
+    }
+}
+
+/// Order of the corners of a triangle as seen from its front
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Winding {
+    /// Used by OBJ and glTF
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Why a triangle is not drawn
+enum Skipped {
+    Outside,
+    Backface,
+    /// Without area, or too large for the rasterizer
+    Degenerate,
+}
+
+/// Transforms a view space position to screen space (pixel coordinates of a `size` image),
+/// keeping the view space depth
+fn view_to_screen(
+    view_vertex: Vector3<f64>,
+    projection: &Matrix4,
+    size: Vector2<f64>,
+) -> Vector3<f64> {
+    let ndc = (*projection * Vector4::from_vector3(view_vertex, 1.)).to_cartesian();
+
+    // Normalized device coordinates have y up, pixel rows go down
+    Vector3::new(
+        (ndc.x + 1.) / 2. * size.x,
+        (1. - ndc.y) / 2. * size.y,
+        view_vertex.z,
+    )
+}
+
+/// View space position of a corner of a cut triangle, and the weights of the triangle's
+/// vertices there
+type ClipCorner = (Vector3<f64>, Vector3<f64>);
+
+/// Cuts off the part of a triangle in front of the near plane, at view depth `-near`. Returns
+/// the corners of what remains in order, up to 4 of them
+fn clip_near(view: [Vector3<f64>; 3], near: f64) -> ([ClipCorner; 4], usize) {
+    let weights = [
+        Vector3::new(1., 0., 0.),
+        Vector3::new(0., 1., 0.),
+        Vector3::new(0., 0., 1.),
+    ];
+    let mut polygon = [(Vector3::default(), Vector3::default()); 4];
+    let mut len = 0;
+
+    for idx in 0..3 {
+        let next = (idx + 1) % 3;
+        let (from, to) = (view[idx], view[next]);
+        let from_inside = from.z <= -near;
+
+        if from_inside {
+            polygon[len] = (from, weights[idx]);
+            len += 1;
+        }
+
+        // The edge crosses the plane
+        if from_inside != (to.z <= -near) {
+            let t = (-near - from.z) / (to.z - from.z);
+            polygon[len] = (from.lerp(&to, t), weights[idx].lerp(&weights[next], t));
+            len += 1;
+        }
+    }
+
+    (polygon, len)
+}
+
 /// Replicates a pixel for `scale` amount of times
 fn render_scaled<T: FnMut(usize, usize)>(point: (usize, usize), scale: usize, mut callback: T) {
     (0..scale).for_each(|y_offset| {
@@ -294,22 +1578,6 @@ fn render_scaled<T: FnMut(usize, usize)>(point: (usize, usize), scale: usize, mu
     });
 }
 
-/// Converts a color matrix to a byte array and returns an owned payload
-fn buf_to_payload(frame_buf: &[Vec<Color>]) -> Cow<[u8]> {
-    let mut payload: Vec<u8> = Vec::with_capacity(4 * frame_buf.len());
-
-    for row in frame_buf.iter() {
-        for color in row {
-            payload.push(color.red);
-            payload.push(color.green);
-            payload.push(color.blue);
-            payload.push(color.alpha);
-        }
-    }
-
-    Cow::Owned(payload)
-}
-
 /// Returns the terminal size
 fn get_term_size() -> winsize {
     ioctl_read_bad!(tiocgwinsz, libc::TIOCGWINSZ, winsize);
@@ -326,7 +1594,9 @@ fn get_term_size() -> winsize {
     }
 }
 
+/// RGBA pixel, laid out as the 4 bytes the terminal expects
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[repr(C)]
 pub struct Color {
     pub red: u8,
     pub green: u8,
@@ -349,6 +1619,55 @@ impl Color {
 
         Color::new(rng.random(), rng.random(), rng.random(), 0xff)
     }
+
+    /// Combines the color with `background`, the one behind it. The alphas add up the same way
+    /// for every blended mode
+    pub fn blend(self, background: Color, mode: BlendMode) -> Self {
+        if mode == BlendMode::Opaque {
+            return self;
+        }
+
+        let alpha = self.alpha as u16;
+        let scale = |value: u16, factor: u16| (value * factor + 127) / 255;
+
+        let channel = |source: u8, back: u8| {
+            let (source, back) = (source as u16, back as u16);
+            let blended = match mode {
+                BlendMode::Opaque => source,
+                BlendMode::Alpha => scale(source, alpha) + scale(back, 255 - alpha),
+                BlendMode::Additive => (back + scale(source, alpha)).min(255),
+                BlendMode::Multiply => scale(scale(source, back), alpha) + scale(back, 255 - alpha),
+            };
+            blended as u8
+        };
+
+        Color::new(
+            channel(self.red, background.red),
+            channel(self.green, background.green),
+            channel(self.blue, background.blue),
+            (alpha + scale(background.alpha as u16, 255 - alpha)) as u8,
+        )
+    }
+
+    /// The same color, with its alpha scaled by `coverage`
+    fn faded(self, coverage: f64) -> Self {
+        Color {
+            alpha: (self.alpha as f64 * coverage).round() as u8,
+            ..self
+        }
+    }
+
+    /// Multiplies each channel with the one of `other`, treating them as values in [0, 1]
+    pub fn multiply(self, other: Color) -> Self {
+        let channel = |a: u8, b: u8| ((a as u16 * b as u16 + 127) / 255) as u8;
+
+        Color::new(
+            channel(self.red, other.red),
+            channel(self.green, other.green),
+            channel(self.blue, other.blue),
+            channel(self.alpha, other.alpha),
+        )
+    }
 }
 
 impl TryFrom<&str> for Color {
@@ -387,6 +1706,568 @@ impl TryFrom<&str> for Color {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        model::primitives::{plane, uv_sphere},
+        scene::Node,
+    };
+    use std::sync::Arc;
+
+    /// Model of a single triangle with the given corners and color
+    fn triangle(corners: [(f64, f64, f64); 3], color: Color) -> Model {
+        Model::new(
+            corners
+                .iter()
+                .map(|&(x, y, z)| Vector3::new(x, y, z))
+                .collect(),
+            vec![[0, 1, 2]],
+            vec![color],
+        )
+    }
+
+    #[test]
+    fn test_frame() {
+        let screen = Screen::new(64, 32);
+        let sphere = BoundingSphere {
+            center: Vector3::new(1., 2., 3.),
+            radius: 2.,
+        };
+
+        let mut camera = Camera::default().with_transform(&Transform::new(0.3, -0.2));
+        screen.frame(&sphere, &mut camera);
+
+        // The sphere is centered in front of the camera, touching the top and bottom edges
+        let center = camera.world_to_view(sphere.center);
+        let Projection::Perspective { fov } = camera.projection else {
+            unreachable!()
+        };
+        assert!(center.x.abs() < 1e-9 && center.y.abs() < 1e-9);
+        assert!((center.z + sphere.radius / (fov / 2.).sin()).abs() < 1e-9);
+
+        // An orthographic camera fits the sphere by its size instead
+        let mut camera = Camera::orthographic(1.);
+        screen.frame(&sphere, &mut camera);
+        assert_eq!(camera.projection, Projection::Orthographic { extent: 4. });
+        assert!(camera.world_to_view(sphere.center).z < -sphere.radius);
+    }
+
+    #[test]
+    fn test_level_of_detail() {
+        let screen = Screen::new(512, 512);
+        let model = uv_sphere(1., 32, 16).with_lods(3);
+        let camera = Camera::default();
+        let sphere =
+            |transform: &Transform| model.bounding_sphere().transform(&transform.to_matrix());
+
+        let mut transform = Transform {
+            position: Vector3::new(0., 0., -2.),
+            ..Default::default()
+        };
+        assert!(std::ptr::eq(
+            screen.level_of_detail(&camera, &model, &sphere(&transform)),
+            &model
+        ));
+
+        // Far away the sphere covers a few pixels and the simplest version is enough
+        transform.position = Vector3::new(0., 0., -200.);
+        assert!(std::ptr::eq(
+            screen.level_of_detail(&camera, &model, &sphere(&transform)),
+            &model.lods[2]
+        ));
+    }
+
+    #[test]
+    fn test_render_orientation() {
+        let mut screen = Screen::new(32, 32);
+        let mut camera = Camera {
+            position: Vector3::new(0., 0., 2.),
+            ..Default::default()
+        };
+
+        // Counter clockwise triangle in the upper right quarter, facing the camera
+        let triangle = triangle(
+            [(0.1, 0.1, 0.), (0.8, 0.1, 0.), (0.1, 0.8, 0.)],
+            Color::new(0xff, 0, 0, 0xff),
+        );
+
+        let drawn = |screen: &Screen| {
+            let pixels = |rows: std::ops::Range<usize>, cols: std::ops::Range<usize>| {
+                rows.flat_map(|y| cols.clone().map(move |x| (x, y)))
+                    .filter(|&(x, y)| screen.pixel(x, y) != Some(Color::default()))
+                    .count()
+            };
+            (pixels(0..16, 16..32), pixels(0..32, 0..32))
+        };
+
+        screen.render(&camera, &triangle, &Transform::default());
+        let (upper_right, total) = drawn(&screen);
+        assert!(upper_right > 0 && upper_right == total);
+
+        // Seen from behind it faces away and is skipped
+        screen.clear();
+        camera.orbit(Vector3::default(), f64::consts::PI, 0.);
+        screen.render(&camera, &triangle, &Transform::default());
+        assert_eq!(drawn(&screen).1, 0);
+
+        // Past the far plane nothing is drawn either
+        screen.clear();
+        camera.orbit(Vector3::default(), f64::consts::PI, 0.);
+        camera.far = 1.;
+        screen.render(&camera, &triangle, &Transform::default());
+        assert_eq!(drawn(&screen).1, 0);
+    }
+
+    #[test]
+    fn test_cull_mode() {
+        let mut screen = Screen::new(32, 32);
+        let triangle = triangle(
+            [(-0.5, -0.5, 0.), (0.5, -0.5, 0.), (0., 0.5, 0.)],
+            Color::new(0xff, 0, 0, 0xff),
+        );
+
+        // Number of pixels drawn looking at the front of the triangle, and then at its back
+        let mut drawn = |cull_mode: CullMode, winding: Winding| {
+            screen.cull_mode = cull_mode;
+            screen.winding = winding;
+
+            [2., -2.].map(|z| {
+                let mut camera = Camera {
+                    position: Vector3::new(0., 0., z),
+                    ..Default::default()
+                };
+                camera.look_at(Vector3::default(), Vector3::new(0., 1., 0.));
+
+                screen.clear();
+                screen.render(&camera, &triangle, &Transform::default());
+                screen
+                    .frame_buf()
+                    .as_slice()
+                    .iter()
+                    .filter(|&&color| color != Color::default())
+                    .count()
+            })
+        };
+
+        let [front, back] = drawn(CullMode::None, Winding::CounterClockwise);
+        assert!(front > 0 && front == back);
+        assert_eq!(drawn(CullMode::Back, Winding::CounterClockwise), [front, 0]);
+        assert_eq!(
+            drawn(CullMode::Front, Winding::CounterClockwise),
+            [0, front]
+        );
+        assert_eq!(drawn(CullMode::Back, Winding::Clockwise), [0, front]);
+    }
+
+    #[test]
+    fn test_render_scene() {
+        let mut screen = Screen::new(32, 32);
+        let camera = Camera {
+            position: Vector3::new(0., 0., 3.),
+            ..Default::default()
+        };
+
+        let triangle = triangle(
+            [(-0.5, -0.5, 0.), (0.5, -0.5, 0.), (0., 0.5, 0.)],
+            Color::new(0xff, 0xff, 0xff, 0xff),
+        );
+        let placed = |x: f64| {
+            let transform = Transform {
+                position: Vector3::new(x, 0., 0.),
+                ..Default::default()
+            };
+            Node::new(transform).with_model(triangle.clone())
+        };
+        let red = Color::new(0xff, 0, 0, 0xff);
+        let green = Color::new(0, 0xff, 0, 0xff);
+        let material = |color| Material {
+            color,
+            ..Default::default()
+        };
+
+        // Children are placed and scaled by their parent, and use its material unless they have
+        // their own
+        let mut parent = placed(-1.).with_material(material(red));
+        parent.transform.scale = Vector3::new(0.5, 0.5, 0.5);
+        let parent = parent
+            .with_child(placed(4.))
+            .with_child(placed(2.).with_material(material(green)));
+        screen.render_scene(&camera, &Scene::new().with_node(parent));
+
+        // At x = -1, 1 and 0 in the world
+        let row = screen.frame_buf().row(16);
+        assert_eq!((row[6], row[25], row[16]), (red, red, green));
+        assert_eq!((row[11], row[21]), (Color::default(), Color::default()));
+        assert_eq!(screen.stats().models_drawn, 3);
+    }
+
+    #[test]
+    fn test_render_instanced() {
+        let mut screen = Screen::new(32, 32);
+        let camera = Camera {
+            position: Vector3::new(0., 0., 3.),
+            ..Default::default()
+        };
+
+        let white = Color::new(0xff, 0xff, 0xff, 0xff);
+        let triangle = triangle([(-0.5, -0.5, 0.), (0.5, -0.5, 0.), (0., 0.5, 0.)], white);
+
+        // One copy on each side, and one behind the camera
+        let transforms = [-1., 1., 10.].map(|offset| Transform {
+            position: Vector3::new(offset, 0., offset.max(0.) * 0.5),
+            ..Default::default()
+        });
+        let red = Color::new(0xff, 0, 0, 0xff);
+        let green = Color::new(0, 0xff, 0, 0xff);
+        screen.render_instanced(&camera, &triangle, &transforms, &[red, green, white]);
+
+        let colors: Vec<Color> = screen
+            .frame_buf()
+            .row(16)
+            .iter()
+            .copied()
+            .filter(|&color| color != Color::default())
+            .collect();
+        assert!(colors.contains(&red) && colors.contains(&green));
+        assert!(!colors.contains(&white));
+        assert!(
+            screen.frame_buf().row(16)[..16]
+                .iter()
+                .all(|&color| color != green)
+        );
+
+        // The copy behind the camera never reaches the rasterizer
+        let stats = screen.stats();
+        assert_eq!((stats.models_drawn, stats.models_culled), (2, 1));
+        assert_eq!(stats.triangles_drawn, 2, "{stats:?}");
+    }
+
+    #[test]
+    fn test_near_plane_clipping() {
+        // A floor reaching far behind the camera, red on the left and blue on the right
+        let red = Color::new(0xff, 0, 0, 0xff);
+        let blue = Color::new(0, 0, 0xff, 0xff);
+        let mut floor = plane(100., 100., 1, 1);
+        floor.texture = Some(Arc::new(Texture::new(2, 1, vec![red, blue])));
+
+        let camera = Camera {
+            position: Vector3::new(0., 1., 0.),
+            ..Default::default()
+        };
+        let mut screen = Screen::new(32, 32);
+        screen.render(&camera, &floor, &Transform::default());
+
+        // Cut at the near plane instead of dropped. The diagonal of the floor passes below the
+        // camera, so its other triangle is beside the view
+        let stats = *screen.stats();
+        assert_eq!(
+            (
+                stats.triangles_drawn,
+                stats.triangles_outside,
+                stats.triangles_skipped
+            ),
+            (1, 1, 0)
+        );
+        for x in 0..32 {
+            let expected = if x < 16 { red } else { blue };
+            assert_eq!(screen.pixel(x, 31), Some(expected));
+            assert_eq!(screen.pixel(x, 17), Some(expected));
+            assert_eq!(screen.pixel(x, 15), Some(Color::default()));
+        }
+
+        // At the depth where the ray through the center of the bottom row hits the floor
+        let half_height = (f64::consts::FRAC_PI_3 / 2.).tan();
+        let ray_y = half_height * (1. - 31.5 / 16.);
+        let depth = screen.depth_buf()[(16, 31)] as f64;
+        assert!((depth - 1. / ray_y).abs() < 1e-3, "{depth}");
+
+        // Edges are cut the same way
+        screen.polygon_mode = PolygonMode::Wireframe;
+        screen.clear();
+        screen.render(&camera, &floor, &Transform::default());
+        assert!(
+            screen
+                .frame_buf()
+                .as_slice()
+                .iter()
+                .any(|&pixel| pixel != Color::default())
+        );
+    }
+
+    #[test]
+    fn test_frustum_culling() {
+        let mut screen = Screen::new(64, 64);
+        let model = uv_sphere(1., 16, 8);
+        let camera = Camera::default();
+
+        // Half of the sphere hangs past the right edge of the screen
+        let mut transform = Transform::default();
+        let half_width = (f64::consts::FRAC_PI_3 / 2.).tan() * 4.;
+        transform.position = Vector3::new(half_width, 0., -4.);
+        screen.render(&camera, &model, &transform);
+
+        let stats = *screen.stats();
+        assert_eq!((stats.models_drawn, stats.models_culled), (1, 0));
+        assert!(stats.triangles_drawn > 0 && stats.triangles_outside > 0);
+        assert!(stats.triangles_backface > 0);
+        assert_eq!(
+            stats.triangles_drawn
+                + stats.triangles_outside
+                + stats.triangles_backface
+                + stats.triangles_skipped,
+            model.triangles.len()
+        );
+
+        // Entirely out of view, or beyond the far plane
+        transform.position = Vector3::new(3. * half_width, 0., -4.);
+        screen.render(&camera, &model, &transform);
+        let mut far_camera = camera;
+        far_camera.far = 2.;
+        transform.position = Vector3::new(0., 0., -4.);
+        screen.render(&far_camera, &model, &transform);
+        assert_eq!(screen.stats().models_culled, 2);
+
+        screen.clear();
+        assert_eq!(*screen.stats(), RenderStats::default());
+    }
+
+    #[test]
+    fn test_multithreaded() {
+        let monkey =
+            crate::model::obj::load_obj("models/monkey.obj").expect("Failed to read model data");
+        let sphere = uv_sphere(1., 32, 16);
+        assert!(monkey.triangles.len() >= PARALLEL_MIN_TRIANGLES);
+
+        // A height that is not a multiple of the tiles, and overlapping models
+        let render = |multithreaded: bool| {
+            let mut screen = Screen::new(100, 70);
+            screen.scale(2);
+            screen.multithreaded = multithreaded;
+
+            let camera = Camera {
+                position: Vector3::new(0., 0., 3.),
+                ..Default::default()
+            };
+            screen.render(&camera, &monkey, &Transform::new(0.3, 0.2));
+            let transform = Transform {
+                position: Vector3::new(0.5, -0.3, -0.5),
+                ..Default::default()
+            };
+            screen.render(&camera, &sphere, &transform);
+
+            (screen.frame_buf, screen.depth_buf, screen.stats)
+        };
+
+        // Pixel for pixel the same, with the same depths
+        let parallel = render(true);
+        assert!(
+            parallel
+                .0
+                .as_slice()
+                .iter()
+                .any(|&color| color != Color::default())
+        );
+        assert!(parallel == render(false));
+    }
+
+    #[test]
+    fn test_anti_aliasing() {
+        let white = Color::new(0xff, 0xff, 0xff, 0xff);
+        let triangle = triangle([(-0.7, -0.6, 0.), (0.8, -0.4, 0.), (-0.1, 0.7, 0.)], white);
+
+        // Covered area in pixels, counting partly covered pixels by their alpha, and the number
+        // of those pixels
+        let render = |anti_aliasing: AntiAliasing| {
+            let mut screen = Screen::new(32, 32);
+            screen.scale(2);
+            screen.anti_aliasing(anti_aliasing);
+            let camera = Camera {
+                position: Vector3::new(0., 0., 2.),
+                ..Default::default()
+            };
+            screen.render(&camera, &triangle, &Transform::default());
+            screen.resolve();
+
+            let pixels: Vec<Color> = (0..32)
+                .flat_map(|y| (0..32).map(move |x| (x, y)))
+                .map(|(x, y)| screen.pixel(x, y).unwrap())
+                .collect();
+            let area = pixels
+                .iter()
+                .map(|color| color.alpha as f64 / 255.)
+                .sum::<f64>();
+            let partial = pixels
+                .iter()
+                .filter(|color| color.alpha != 0 && color.alpha != 0xff)
+                .count();
+            (area, partial)
+        };
+
+        let (area, partial) = render(AntiAliasing::None);
+        assert_eq!(partial, 0);
+
+        // The edges blend with the background, covering about the same area
+        for anti_aliasing in [
+            AntiAliasing::Supersampling {
+                factor: 4,
+                filter: Filter::Box,
+            },
+            AntiAliasing::Supersampling {
+                factor: 2,
+                filter: Filter::Tent,
+            },
+            AntiAliasing::Multisampling { samples: 4 },
+            AntiAliasing::Multisampling { samples: 8 },
+        ] {
+            let (smooth_area, smooth_partial) = render(anti_aliasing);
+            assert!(smooth_partial > 0, "{anti_aliasing:?}");
+            assert!(
+                (smooth_area - area).abs() < area * 0.05,
+                "{anti_aliasing:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_filter_weights() {
+        assert_eq!(Filter::Box.weights(2), (0, vec![1., 1.]));
+
+        // Symmetric around the center of the pixel, reaching 0 at the neighbouring centers
+        let (offset, weights) = Filter::Tent.weights(2);
+        assert_eq!(offset, -2);
+        assert_eq!(weights, vec![0., 0.25, 0.75, 0.75, 0.25, 0.]);
+    }
+
+    #[test]
+    fn test_transparency() {
+        let at_depth =
+            |z: f64, color: Color| triangle([(-1., -1., z), (1., -1., z), (0., 1., z)], color);
+        let blue = Color::new(0, 0, 0xff, 0xff);
+        let red = Color::new(0xff, 0, 0, 0x80);
+        let green = Color::new(0, 0xff, 0, 0x80);
+
+        // The closest transparent triangle comes first, and the opaque one last
+        let scene = |transparent: [(f64, Color); 2]| {
+            let mut scene = Scene::new();
+            for (z, color) in transparent {
+                scene = scene.with_node(
+                    Node::default()
+                        .with_model(at_depth(z, color))
+                        .with_material(Material::transparent(Color::new(0xff, 0xff, 0xff, 0xff))),
+                );
+            }
+            scene.with_node(Node::default().with_model(at_depth(-1., blue)))
+        };
+
+        let camera = Camera {
+            position: Vector3::new(0., 0., 2.),
+            ..Default::default()
+        };
+        let mut screen = Screen::new(16, 16);
+        screen.render_scene(&camera, &scene([(0., green), (-0.5, red)]));
+
+        // Blended from back to front, without hiding what is drawn later
+        let expected = green.blend(red.blend(blue, BlendMode::Alpha), BlendMode::Alpha);
+        assert_eq!(screen.pixel(8, 8), Some(expected));
+        assert_eq!(screen.depth_buf()[(8, 8)], -3.);
+
+        // Weighted blending does not depend on the order of the triangles
+        screen.transparency = Transparency::WeightedBlended;
+        let mut render = |transparent| {
+            screen.clear();
+            screen.render_scene(&camera, &scene(transparent));
+            screen.resolve();
+            screen.pixel(8, 8).unwrap()
+        };
+        let blended = render([(0., green), (-0.5, red)]);
+        assert_eq!(blended, render([(-0.5, red), (0., green)]));
+        assert!(blended.red > 0 && blended.green > 0 && blended.blue > 0);
+        assert_eq!(blended.alpha, 0xff);
+
+        // Also when each model is rendered by itself, as they are only blended once the frame
+        // is resolved
+        let mut render_apart = |transparent: [(f64, Color); 2]| {
+            screen.clear();
+            screen.render(&camera, &at_depth(-1., blue), &Transform::default());
+            for (z, color) in transparent {
+                let node = Node::default()
+                    .with_model(at_depth(z, color))
+                    .with_material(Material::transparent(Color::new(0xff, 0xff, 0xff, 0xff)));
+                screen.render_scene(&camera, &Scene::new().with_node(node));
+            }
+            screen.resolve();
+            screen.pixel(8, 8).unwrap()
+        };
+        assert_eq!(render_apart([(0., green), (-0.5, red)]), blended);
+        assert_eq!(render_apart([(-0.5, red), (0., green)]), blended);
+    }
+
+    #[test]
+    fn test_polygon_modes() {
+        let sized = |z: f64, size: f64| {
+            let corners = [(-size, -size, z), (size, -size, z), (0., size, z)];
+            triangle(corners, Color::new(0, 0, 0xff, 0xff))
+        };
+        let scene = |models: Vec<Model>| {
+            models.into_iter().fold(Scene::new(), |scene, model| {
+                scene.with_node(Node::default().with_model(model))
+            })
+        };
+        let camera = Camera {
+            position: Vector3::new(0., 0., 2.),
+            ..Default::default()
+        };
+        let mut screen = Screen::new(32, 32);
+        let mut render = |mode, scene: &Scene| {
+            screen.polygon_mode = mode;
+            screen.clear();
+            screen.render_scene(&camera, scene);
+            screen.frame_buf().clone()
+        };
+        let lines = |frame: &Buffer<Color>| {
+            frame
+                .as_slice()
+                .iter()
+                .filter(|pixel| pixel.red > 0)
+                .count()
+        };
+
+        // Only the outline is drawn, in white, and points only around the corners
+        let single = scene(vec![sized(0., 1.)]);
+        let wireframe = render(PolygonMode::Wireframe, &single);
+        assert!(lines(&wireframe) > 0);
+        assert_eq!(wireframe[(16, 16)], Color::default());
+        let points = render(PolygonMode::Points, &single);
+        assert!(lines(&points) > 0 && lines(&points) < lines(&wireframe) / 4);
+
+        // The edges are drawn over the filled triangle
+        let filled = render(PolygonMode::FillAndWireframe, &single);
+        assert_eq!(lines(&filled), lines(&wireframe));
+        assert_eq!(filled[(16, 16)], Color::new(0, 0, 0xff, 0xff));
+
+        // A smaller triangle behind the first one only shows through in wireframe
+        let hidden = scene(vec![sized(-1., 0.5), sized(0., 1.)]);
+        assert_ne!(render(PolygonMode::Wireframe, &hidden), wireframe);
+        assert_eq!(render(PolygonMode::HiddenLine, &hidden), wireframe);
+    }
+
+    #[test]
+    fn test_color_blend() {
+        let background = Color::new(0, 0x80, 0xff, 0x80);
+        let color = Color::new(0xff, 0xff, 0, 0x80);
+
+        assert_eq!(color.blend(background, BlendMode::Opaque), color);
+        assert_eq!(
+            color.blend(background, BlendMode::Alpha),
+            Color::new(0x80, 0xc0, 0x7f, 0xc0)
+        );
+        assert_eq!(
+            color.blend(background, BlendMode::Additive),
+            Color::new(0x80, 0xff, 0xff, 0xc0)
+        );
+        assert_eq!(
+            color.blend(background, BlendMode::Multiply),
+            Color::new(0, 0x80, 0x7f, 0xc0)
+        );
+    }
 
     #[test]
     fn test_color_from_str_noalpha() {
@@ -412,6 +2293,17 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_color_multiply() {
+        let color = Color::new(0xff, 0x80, 0x00, 0xff);
+
+        assert_eq!(color.multiply(Color::new(0xff, 0xff, 0xff, 0xff)), color);
+        assert_eq!(
+            color.multiply(Color::new(0x80, 0x80, 0xff, 0x00)),
+            Color::new(0x80, 0x40, 0x00, 0x00)
+        );
+    }
+
     #[test]
     fn test_color_from_str_error() {
         assert_eq!(
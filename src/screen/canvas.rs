@@ -0,0 +1,383 @@
+use std::ops::Range;
+
+use crate::{buffer::Buffer, font::Font, raster, vector::vector2::Vector2};
+
+use super::{BlendMode, Color, Screen, TextStyle, render_scaled};
+
+/// Drawing in 2D over the image, in pixels of the screen from its top left corner. Shapes are
+/// blended over what is already drawn by their alpha and clipped to the screen. The frame is
+/// resolved before they are drawn on it, so with anti-aliasing they have to come after rendering
+impl Screen {
+    /// Blends a single pixel
+    pub fn draw_point(&mut self, (x, y): (i64, i64), color: Color) {
+        self.blend_pixel(x, y, color, BlendMode::Alpha);
+    }
+
+    /// Draws a one pixel wide line between two pixels, both included, with Bresenham's
+    /// algorithm
+    pub fn draw_line(&mut self, from: (i64, i64), to: (i64, i64), color: Color) {
+        raster::bresenham(from, to, |x, y| {
+            self.blend_pixel(x, y, color, BlendMode::Alpha)
+        });
+    }
+
+    /// Draws a one pixel wide line with smooth edges, with Xiaolin Wu's algorithm. The ends
+    /// can be anywhere inside of the pixels, where (0.5, 0.5) is the center of the first one
+    pub fn draw_line_smooth(&mut self, from: Vector2<f64>, to: Vector2<f64>, color: Color) {
+        raster::wu_line(from, to, |x, y, coverage| {
+            self.blend_pixel(x, y, color.faded(coverage), BlendMode::Alpha)
+        });
+    }
+
+    /// Draws the outline of a rectangle of `width` by `height` pixels, from its top left one
+    pub fn draw_rect(&mut self, (x, y): (i64, i64), width: usize, height: usize, color: Color) {
+        let (width, height) = (width as i64, height as i64);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // Sides without the corners, which belong to the top and bottom rows
+        self.blend_span(y, x..x + width, color);
+        if height > 1 {
+            self.blend_span(y + height - 1, x..x + width, color);
+        }
+        for row in y + 1..y + height - 1 {
+            self.blend_pixel(x, row, color, BlendMode::Alpha);
+            if width > 1 {
+                self.blend_pixel(x + width - 1, row, color, BlendMode::Alpha);
+            }
+        }
+    }
+
+    /// Fills a rectangle of `width` by `height` pixels, from its top left one
+    pub fn fill_rect(&mut self, (x, y): (i64, i64), width: usize, height: usize, color: Color) {
+        for row in y..y + height as i64 {
+            self.blend_span(row, x..x + width as i64, color);
+        }
+    }
+
+    /// Draws the outline of a circle of `radius` pixels around the `center` pixel
+    pub fn draw_circle(&mut self, center: (i64, i64), radius: usize, color: Color) {
+        // Each pixel of an eighth of the circle is mirrored to the others, which repeats the
+        // ones on the axes and diagonals
+        let mut pixels = Vec::new();
+        raster::circle_octant(radius as i64, |x, y| {
+            for (x, y) in [(x, y), (y, x)] {
+                pixels.extend([(x, y), (-x, y), (x, -y), (-x, -y)]);
+            }
+        });
+        pixels.sort_unstable();
+        pixels.dedup();
+
+        for (x, y) in pixels {
+            self.blend_pixel(center.0 + x, center.1 + y, color, BlendMode::Alpha);
+        }
+    }
+
+    /// Fills a circle of `radius` pixels around the `center` pixel, up to the pixels of its
+    /// outline
+    pub fn fill_circle(&mut self, center: (i64, i64), radius: usize, color: Color) {
+        // Half of the width of each row, away from the center
+        let mut half_widths = vec![0; radius + 1];
+        raster::circle_octant(radius as i64, |x, y| {
+            half_widths[y as usize] = half_widths[y as usize].max(x);
+            half_widths[x as usize] = half_widths[x as usize].max(y);
+        });
+
+        for (y, half_width) in half_widths.into_iter().enumerate() {
+            let columns = center.0 - half_width..center.0 + half_width + 1;
+            self.blend_span(center.1 - y as i64, columns.clone(), color);
+            if y > 0 {
+                self.blend_span(center.1 + y as i64, columns, color);
+            }
+        }
+    }
+
+    /// Draws the closed outline through the given pixels
+    pub fn draw_polygon(&mut self, corners: &[(i64, i64)], color: Color) {
+        // Consecutive edges share their corner, which is only drawn once
+        let mut pixels = Vec::new();
+        for (idx, &from) in corners.iter().enumerate() {
+            let to = corners[(idx + 1) % corners.len()];
+            raster::bresenham(from, to, |x, y| pixels.push((x, y)));
+        }
+        pixels.sort_unstable();
+        pixels.dedup();
+
+        for (x, y) in pixels {
+            self.blend_pixel(x, y, color, BlendMode::Alpha);
+        }
+    }
+
+    /// Fills the polygon through the given pixels, with the pixels whose center is inside of
+    /// it. Parts of the polygon overlapping an odd number of times are filled
+    pub fn fill_polygon(&mut self, corners: &[(i64, i64)], color: Color) {
+        let corners: Vec<_> = corners
+            .iter()
+            .map(|&(x, y)| Vector2::new(x as f64 + 0.5, y as f64 + 0.5))
+            .collect();
+
+        raster::polygon(&corners, 0..self.height, |y, columns| {
+            self.blend_span(y as i64, columns, color)
+        });
+    }
+
+    /// Copies an image with its top left corner on pixel `(x, y)`, combining each of its
+    /// pixels with the one below by `blend`. `BlendMode::Opaque` replaces the pixels, and
+    /// `BlendMode::Alpha` uses the alpha of the image
+    pub fn blit(&mut self, image: &Buffer<Color>, (x, y): (i64, i64), blend: BlendMode) {
+        for row in 0..image.height {
+            for (column, &color) in image.row(row).iter().enumerate() {
+                self.blend_pixel(x + column as i64, y + row as i64, color, blend);
+            }
+        }
+    }
+
+    /// Draws `text` with its anchor on pixel `(x, y)`, starting a new line after each '\n'
+    pub fn draw_text(&mut self, font: &Font, text: &str, (x, y): (i64, i64), style: TextStyle) {
+        let scale = style.scale;
+        let (width, height) = font.measure(text);
+        let (horizontal, vertical) = style.anchor.halves();
+
+        let mut top = y - (height * scale * vertical / 2) as i64;
+        for line in text.lines() {
+            let line_width = font.line_width(line) * scale;
+            let mut left = x - (width * scale * horizontal / 2) as i64;
+            left += ((width * scale - line_width) * horizontal / 2) as i64;
+
+            for glyph in line.chars().filter_map(|character| font.glyph(character)) {
+                let (offset_x, offset_y) = glyph.offset;
+                for glyph_y in 0..glyph.bitmap.height {
+                    for (glyph_x, &set) in glyph.bitmap.row(glyph_y).iter().enumerate() {
+                        // Each pixel of the glyph covers `scale` by `scale` pixels
+                        let corner = (
+                            left + (offset_x + glyph_x as i64) * scale as i64,
+                            top + (offset_y + glyph_y as i64) * scale as i64,
+                        );
+                        if set {
+                            self.fill_rect(corner, scale, scale, style.color);
+                        }
+                    }
+                }
+                left += (glyph.advance * scale) as i64;
+            }
+            top += (font.line_height * scale) as i64;
+        }
+    }
+
+    /// Combines `color` with a pixel of the screen, skipping pixels outside of it
+    fn blend_pixel(&mut self, x: i64, y: i64, color: Color, blend: BlendMode) {
+        self.resolve();
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+
+        let frame = &mut self.frame_buf;
+        render_scaled(
+            (x as usize, y as usize),
+            self.scale,
+            |scaled_x, scaled_y| {
+                let target = &mut frame[(scaled_x, scaled_y)];
+                *target = color.blend(*target, blend);
+            },
+        );
+    }
+
+    /// Blends the given columns of row `y`
+    fn blend_span(&mut self, y: i64, columns: Range<i64>, color: Color) {
+        let columns = columns.start.max(0)..columns.end.min(self.width as i64);
+        for x in columns {
+            self.blend_pixel(x, y, color, BlendMode::Alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::screen::Anchor;
+
+    /// Pixels of the screen that are not empty, as text with one line per row
+    fn drawn(screen: &Screen) -> String {
+        (0..screen.height)
+            .map(|y| {
+                (0..screen.width)
+                    .map(|x| match screen.pixel(x, y).unwrap() == Color::default() {
+                        true => '.',
+                        false => '#',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_shapes() {
+        let color = Color::new(0xff, 0, 0, 0xff);
+        let mut screen = Screen::new(7, 7);
+        screen.scale(2);
+
+        screen.draw_rect((1, 1), 5, 4, color);
+        screen.draw_line((-2, 6), (8, 6), color);
+        assert_eq!(
+            drawn(&screen),
+            [
+                ".......", ".#####.", ".#...#.", ".#...#.", ".#####.", ".......", "#######"
+            ]
+            .join("\n")
+        );
+
+        screen.clear();
+        screen.draw_circle((3, 3), 3, color);
+        let outline = drawn(&screen);
+        assert_eq!(
+            outline,
+            [
+                "..###..", ".#...#.", "#.....#", "#.....#", "#.....#", ".#...#.", "..###.."
+            ]
+            .join("\n")
+        );
+
+        // The filled circle covers its outline
+        screen.clear();
+        screen.fill_circle((3, 3), 3, color);
+        let filled = drawn(&screen);
+        assert!(
+            outline
+                .chars()
+                .zip(filled.chars())
+                .all(|(outline, filled)| outline != '#' || filled == '#')
+        );
+
+        // Pixels on the right and bottom edges of polygons are left to their neighbours
+        let corners = [
+            (2, 0),
+            (5, 0),
+            (7, 2),
+            (7, 5),
+            (5, 7),
+            (2, 7),
+            (0, 5),
+            (0, 2),
+        ];
+        screen.clear();
+        screen.fill_polygon(&corners, color);
+        assert_eq!(
+            drawn(&screen),
+            [
+                "..###..", ".#####.", "#######", "#######", "#######", "#######", ".#####."
+            ]
+            .join("\n")
+        );
+
+        // Overlapping pixels are blended once
+        let half = Color::new(0xff, 0, 0, 0x80);
+        screen.clear();
+        screen.draw_polygon(&[(0, 0), (6, 0), (6, 6)], half);
+        screen.draw_circle((3, 3), 3, half);
+        let once = half.blend(Color::default(), BlendMode::Alpha);
+        assert_eq!(screen.pixel(6, 0), Some(once));
+        assert_eq!(screen.pixel(0, 3), Some(once));
+        assert_eq!(screen.pixel(6, 3), Some(half.blend(once, BlendMode::Alpha)));
+    }
+
+    #[test]
+    fn test_smooth_line() {
+        let mut screen = Screen::new(8, 8);
+        screen.draw_line_smooth(
+            Vector2::new(0.5, 0.5),
+            Vector2::new(7.5, 4.),
+            Color::new(0xff, 0xff, 0xff, 0xff),
+        );
+
+        // Each column is split between the two rows closest to the line
+        for x in 1..7 {
+            let alphas: Vec<_> = (0..8).map(|y| screen.pixel(x, y).unwrap().alpha).collect();
+            let total: u32 = alphas.iter().map(|&alpha| alpha as u32).sum();
+            assert!(total.abs_diff(0xff) <= 1);
+            assert!(alphas.iter().filter(|&&alpha| alpha > 0).count() <= 2);
+        }
+    }
+
+    #[test]
+    fn test_blit() {
+        let mut image = Buffer::new(2, 2, Color::new(0, 0, 0xff, 0x80));
+        image[(0, 0)] = Color::new(0, 0xff, 0, 0xff);
+
+        let background = Color::new(0xff, 0, 0, 0xff);
+        let mut screen = Screen::new(3, 3);
+        screen.scale(2);
+        screen.fill_rect((0, 0), 3, 3, background);
+
+        // Clipped by the screen, and blended by the alpha of the image
+        screen.blit(&image, (2, 2), BlendMode::Alpha);
+        assert_eq!(screen.pixel(2, 2), Some(Color::new(0, 0xff, 0, 0xff)));
+        screen.blit(&image, (-1, -1), BlendMode::Alpha);
+        assert_eq!(
+            screen.pixel(0, 0),
+            Some(Color::new(0, 0, 0xff, 0x80).blend(background, BlendMode::Alpha))
+        );
+        assert_eq!(screen.pixel(1, 1), Some(background));
+
+        // Replaced, without alpha
+        screen.blit(&image, (1, 1), BlendMode::Opaque);
+        assert_eq!(screen.pixel(2, 1), Some(Color::new(0, 0, 0xff, 0x80)));
+        assert_eq!(screen.frame_buf()[(5, 5)], Color::new(0, 0, 0xff, 0x80));
+    }
+
+    #[test]
+    fn test_text() {
+        let font = Font::default();
+        let mut screen = Screen::new(14, 10);
+
+        let style = TextStyle {
+            color: Color::new(0xff, 0, 0, 0xff),
+            ..Default::default()
+        };
+        screen.draw_text(&font, "T1", (0, 0), style);
+        assert_eq!(
+            drawn(&screen),
+            [
+                "#####...#.....",
+                "..#....##.....",
+                "..#.....#.....",
+                "..#.....#.....",
+                "..#.....#.....",
+                "..#.....#.....",
+                "..#....###....",
+                "..............",
+                "..............",
+                "..............",
+            ]
+            .join("\n")
+        );
+
+        // Doubled, and centered on the screen by the middle of its box
+        let style = TextStyle {
+            scale: 2,
+            anchor: Anchor::Center,
+            ..style
+        };
+        screen.clear();
+        screen.draw_text(&font, "-", (7, 5), style);
+        assert_eq!(drawn(&screen).lines().nth(3), Some(".##########..."));
+        assert_eq!(drawn(&screen).lines().nth(4), Some(".##########..."));
+        assert_eq!(drawn(&screen).matches('#').count(), 20);
+
+        // Each line is aligned to the anchor, and the box ends with the space under the last
+        screen.clear();
+        let style = TextStyle {
+            scale: 1,
+            anchor: Anchor::BottomRight,
+            ..style
+        };
+        screen.draw_text(&font, "||\n|", (14, 10), style);
+        let drawn = drawn(&screen);
+        let rows: Vec<_> = drawn.lines().collect();
+        assert_eq!(rows[0], "....#.....#...");
+        assert_eq!(rows[1], "..............");
+        assert_eq!(rows[2], "..........#...");
+        assert_eq!(rows[9], "..............");
+    }
+}
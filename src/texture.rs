@@ -0,0 +1,52 @@
+use crate::{screen::Color, vector::vector2::Vector2};
+
+/// An RGBA image sampled by textured models
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Texture {
+    /// Creates a texture from row-major pixels, starting from the top left corner
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "Texture size does not match the pixel count"
+        );
+
+        Texture {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the texel closest to `uv`, where (0, 0) is the top left corner of the image.
+    /// Coordinates outside of [0, 1] wrap around
+    pub fn sample(&self, uv: Vector2<f64>) -> Color {
+        let x = (uv.x.rem_euclid(1.) * self.width as f64) as usize;
+        let y = (uv.y.rem_euclid(1.) * self.height as f64) as usize;
+
+        self.pixels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample_wraps() {
+        let red = Color::new(0xff, 0, 0, 0xff);
+        let blue = Color::new(0, 0, 0xff, 0xff);
+        let texture = Texture::new(2, 1, vec![red, blue]);
+
+        assert_eq!(texture.sample(Vector2::new(0.25, 0.5)), red);
+        assert_eq!(texture.sample(Vector2::new(0.75, 0.5)), blue);
+        assert_eq!(texture.sample(Vector2::new(1.25, 0.5)), red);
+        assert_eq!(texture.sample(Vector2::new(-0.25, 0.5)), blue);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::vector::vector3::Vector3;
+
+use std::ops::Mul;
+
+/// 3x3 matrix stored row by row, transforming the column vectors on its right
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Matrix3 {
+    pub rows: [[f64; 3]; 3],
+}
+
+impl Matrix3 {
+    pub const IDENTITY: Matrix3 = Matrix3 {
+        rows: [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+    };
+
+    pub fn new(rows: [[f64; 3]; 3]) -> Self {
+        Matrix3 { rows }
+    }
+
+    /// Matrix that maps the x, y and z axes to `i`, `j` and `k`
+    pub fn from_columns(i: Vector3<f64>, j: Vector3<f64>, k: Vector3<f64>) -> Self {
+        Matrix3::new([[i.x, j.x, k.x], [i.y, j.y, k.y], [i.z, j.z, k.z]])
+    }
+
+    pub fn from_scale(scale: Vector3<f64>) -> Self {
+        Matrix3::new([[scale.x, 0., 0.], [0., scale.y, 0.], [0., 0., scale.z]])
+    }
+
+    /// Counter clockwise rotation around the x axis, looking from the positive side
+    pub fn rotation_x(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Matrix3::new([[1., 0., 0.], [0., cos, -sin], [0., sin, cos]])
+    }
+
+    /// Counter clockwise rotation around the y axis, looking from the positive side
+    pub fn rotation_y(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Matrix3::new([[cos, 0., sin], [0., 1., 0.], [-sin, 0., cos]])
+    }
+
+    /// Counter clockwise rotation around the z axis, looking from the positive side
+    pub fn rotation_z(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Matrix3::new([[cos, -sin, 0.], [sin, cos, 0.], [0., 0., 1.]])
+    }
+
+    pub fn column(&self, idx: usize) -> Vector3<f64> {
+        Vector3::new(self.rows[0][idx], self.rows[1][idx], self.rows[2][idx])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Matrix3::from_columns(
+            Vector3::new(self.rows[0][0], self.rows[0][1], self.rows[0][2]),
+            Vector3::new(self.rows[1][0], self.rows[1][1], self.rows[1][2]),
+            Vector3::new(self.rows[2][0], self.rows[2][1], self.rows[2][2]),
+        )
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let [i, j, k] = [0, 1, 2].map(|idx| self.column(idx));
+        i * j.cross(&k)
+    }
+
+    /// Matrix that undoes this one, `None` if it squashes space into a plane or less
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0. || !det.is_finite() {
+            return None;
+        }
+
+        // The rows of the inverse are perpendicular to two columns each
+        let [i, j, k] = [0, 1, 2].map(|idx| self.column(idx));
+        Some(Matrix3::from_columns(j.cross(&k), k.cross(&i), i.cross(&j)).transpose() * (1. / det))
+    }
+}
+
+impl Default for Matrix3 {
+    fn default() -> Self {
+        Matrix3::IDENTITY
+    }
+}
+
+impl Mul for Matrix3 {
+    type Output = Matrix3;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Matrix3::from_columns(
+            self * rhs.column(0),
+            self * rhs.column(1),
+            self * rhs.column(2),
+        )
+    }
+}
+
+impl Mul<Vector3<f64>> for Matrix3 {
+    type Output = Vector3<f64>;
+
+    fn mul(self, rhs: Vector3<f64>) -> Self::Output {
+        let row =
+            |r: usize| self.rows[r][0] * rhs.x + self.rows[r][1] * rhs.y + self.rows[r][2] * rhs.z;
+
+        Vector3::new(row(0), row(1), row(2))
+    }
+}
+
+impl Mul<f64> for Matrix3 {
+    type Output = Matrix3;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Matrix3::new(self.rows.map(|row| row.map(|value| value * rhs)))
+    }
+}
@@ -0,0 +1,179 @@
+use crate::vector::{matrix3::Matrix3, vector3::Vector3, vector4::Vector4};
+
+use std::ops::Mul;
+
+/// 4x4 matrix stored row by row, transforming the column vectors on its right. Points have an
+/// implicit w of 1 and directions of 0
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Matrix4 {
+    pub rows: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub const IDENTITY: Matrix4 = Matrix4 {
+        rows: [
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ],
+    };
+
+    pub fn new(rows: [[f64; 4]; 4]) -> Self {
+        Matrix4 { rows }
+    }
+
+    pub fn from_translation(translation: Vector3<f64>) -> Self {
+        let mut matrix = Matrix4::IDENTITY;
+        matrix.rows[0][3] = translation.x;
+        matrix.rows[1][3] = translation.y;
+        matrix.rows[2][3] = translation.z;
+        matrix
+    }
+
+    pub fn from_scale(scale: Vector3<f64>) -> Self {
+        Matrix3::from_scale(scale).into()
+    }
+
+    /// Upper left 3x3 part, holding the rotation and scale
+    pub fn linear(&self) -> Matrix3 {
+        Matrix3::new([0, 1, 2].map(|row| [0, 1, 2].map(|col| self.rows[row][col])))
+    }
+
+    pub fn translation(&self) -> Vector3<f64> {
+        Vector3::new(self.rows[0][3], self.rows[1][3], self.rows[2][3])
+    }
+
+    pub fn transform_point(&self, point: Vector3<f64>) -> Vector3<f64> {
+        self.linear() * point + self.translation()
+    }
+
+    /// Transforms a direction, which is not affected by the translation
+    pub fn transform_vector(&self, vector: Vector3<f64>) -> Vector3<f64> {
+        self.linear() * vector
+    }
+
+    pub fn transpose(&self) -> Self {
+        Matrix4::new([0, 1, 2, 3].map(|row| [0, 1, 2, 3].map(|col| self.rows[col][row])))
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let (s, c) = self.minors();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// Matrix that undoes this one, `None` if it squashes space into a plane or less
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0. || !det.is_finite() {
+            return None;
+        }
+
+        let a = &self.rows;
+        let (s, c) = self.minors();
+
+        // Adjugate matrix, from the cofactors of each element
+        let adjugate = [
+            [
+                a[1][1] * c[5] - a[1][2] * c[4] + a[1][3] * c[3],
+                -a[0][1] * c[5] + a[0][2] * c[4] - a[0][3] * c[3],
+                a[3][1] * s[5] - a[3][2] * s[4] + a[3][3] * s[3],
+                -a[2][1] * s[5] + a[2][2] * s[4] - a[2][3] * s[3],
+            ],
+            [
+                -a[1][0] * c[5] + a[1][2] * c[2] - a[1][3] * c[1],
+                a[0][0] * c[5] - a[0][2] * c[2] + a[0][3] * c[1],
+                -a[3][0] * s[5] + a[3][2] * s[2] - a[3][3] * s[1],
+                a[2][0] * s[5] - a[2][2] * s[2] + a[2][3] * s[1],
+            ],
+            [
+                a[1][0] * c[4] - a[1][1] * c[2] + a[1][3] * c[0],
+                -a[0][0] * c[4] + a[0][1] * c[2] - a[0][3] * c[0],
+                a[3][0] * s[4] - a[3][1] * s[2] + a[3][3] * s[0],
+                -a[2][0] * s[4] + a[2][1] * s[2] - a[2][3] * s[0],
+            ],
+            [
+                -a[1][0] * c[3] + a[1][1] * c[1] - a[1][2] * c[0],
+                a[0][0] * c[3] - a[0][1] * c[1] + a[0][2] * c[0],
+                -a[3][0] * s[3] + a[3][1] * s[1] - a[3][2] * s[0],
+                a[2][0] * s[3] - a[2][1] * s[1] + a[2][2] * s[0],
+            ],
+        ];
+
+        Some(Matrix4::new(adjugate) * (1. / det))
+    }
+
+    /// Determinants of the 2x2 matrices in the first two rows (`s`) and in the last two (`c`)
+    fn minors(&self) -> ([f64; 6], [f64; 6]) {
+        let a = &self.rows;
+        let minor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+            a[r0][c0] * a[r1][c1] - a[r1][c0] * a[r0][c1]
+        };
+
+        (
+            [
+                minor(0, 1, 0, 1),
+                minor(0, 1, 0, 2),
+                minor(0, 1, 0, 3),
+                minor(0, 1, 1, 2),
+                minor(0, 1, 1, 3),
+                minor(0, 1, 2, 3),
+            ],
+            [
+                minor(2, 3, 0, 1),
+                minor(2, 3, 0, 2),
+                minor(2, 3, 0, 3),
+                minor(2, 3, 1, 2),
+                minor(2, 3, 1, 3),
+                minor(2, 3, 2, 3),
+            ],
+        )
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Matrix4::IDENTITY
+    }
+}
+
+impl From<Matrix3> for Matrix4 {
+    fn from(matrix: Matrix3) -> Self {
+        let mut result = Matrix4::IDENTITY;
+        for (row, values) in matrix.rows.iter().enumerate() {
+            result.rows[row][..3].copy_from_slice(values);
+        }
+        result
+    }
+}
+
+impl Mul for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Matrix4::new([0, 1, 2, 3].map(|row| {
+            [0, 1, 2, 3].map(|col| (0..4).map(|k| self.rows[row][k] * rhs.rows[k][col]).sum())
+        }))
+    }
+}
+
+impl Mul<f64> for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Matrix4::new(self.rows.map(|row| row.map(|value| value * rhs)))
+    }
+}
+
+impl Mul<Vector4<f64>> for Matrix4 {
+    type Output = Vector4<f64>;
+
+    fn mul(self, rhs: Vector4<f64>) -> Self::Output {
+        let row = |r: usize| {
+            let [x, y, z, w] = self.rows[r];
+            x * rhs.x + y * rhs.y + z * rhs.z + w * rhs.w
+        };
+
+        Vector4::new(row(0), row(1), row(2), row(3))
+    }
+}
@@ -1,38 +1,69 @@
+pub mod matrix3;
+pub mod matrix4;
+pub mod quaternion;
 pub mod transform;
 pub mod vector2;
 pub mod vector3;
+pub mod vector4;
 
 use std::ops::Add;
 use std::ops::Mul;
 
-pub trait Vector<T>
+/// Vector with `N` components of type `T`. Components are passed around in fixed size arrays,
+/// so that the operations built on them don't allocate and compile down to plain arithmetic
+pub trait Vector<T, const N: usize>
 where
     T: Clone,
 {
-    fn components(&self) -> Vec<&T>;
+    fn components(&self) -> [T; N];
 
+    fn from_components(components: [T; N]) -> Self;
+
+    /// Applies a function to each component
+    #[inline]
+    fn map<F>(&self, f: F) -> Self
+    where
+        F: FnMut(T) -> T,
+        Self: Sized,
+    {
+        Self::from_components(self.components().map(f))
+    }
+
+    /// Combines the components of two vectors one by one
+    #[inline]
+    fn zip_map<F>(&self, other: &Self, mut f: F) -> Self
+    where
+        F: FnMut(T, T) -> T,
+        Self: Sized,
+    {
+        let mut other = other.components().into_iter();
+        Self::from_components(self.components().map(|c| f(c, other.next().unwrap())))
+    }
+
+    #[inline]
     fn dot(self, other: Self) -> T
     where
         T: Mul<T, Output = T> + Add<T, Output = T> + Default,
         Self: std::marker::Sized,
     {
-        let self_components = self.components();
-        let oth_components = other.components();
-
-        self_components
-            .iter()
-            .zip(oth_components)
-            .fold(T::default(), |acc, (&self_c, other_c)| {
-                acc + self_c.clone() * other_c.clone()
+        self.components()
+            .into_iter()
+            .zip(other.components())
+            .fold(T::default(), |acc, (self_c, other_c)| {
+                acc + self_c * other_c
             })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::vector::vector2::Vector2;
+    use crate::vector::{
+        matrix3::Matrix3, matrix4::Matrix4, quaternion::Quaternion, transform::Transform,
+        vector2::Vector2, vector3::Vector3, vector4::Vector4,
+    };
 
     use super::*;
+    use std::f64::consts::FRAC_PI_2;
 
     #[test]
     fn test_dot_product() {
@@ -43,6 +74,91 @@ mod test {
         assert_eq!(a * b, 6.);
     }
 
+    #[test]
+    fn test_cross_product() {
+        let x = Vector3::new(1., 0., 0.);
+        let y = Vector3::new(0., 1., 0.);
+
+        assert_eq!(x.cross(&y), Vector3::new(0., 0., 1.));
+        assert_eq!(y.cross(&x), Vector3::new(0., 0., -1.));
+        assert_eq!(x.cross(&x), Vector3::default());
+    }
+
+    #[test]
+    fn test_length_and_normalize() {
+        let v = Vector3::new(3., 0., -4.);
+
+        assert_eq!(v.length_squared(), 25.);
+        assert_eq!(v.length(), 5.);
+        assert_eq!(v.normalize(), Vector3::new(0.6, 0., -0.8));
+        assert_eq!(Vector3::<f64>::default().normalize(), Vector3::default());
+        assert_eq!(v.distance(&Vector3::new(3., 1., -4.)), 1.);
+        assert_eq!(Vector2::new(-3., 4.).length(), 5.);
+        assert_eq!(Vector2::new(1., 0.).cross(&Vector2::new(0., 1.)), 1.);
+    }
+
+    #[test]
+    fn test_component_wise() {
+        let a = Vector3::new(1., -2., 3.);
+        let b = Vector3::new(-1., 0., 5.);
+
+        assert_eq!(a.min(&b), Vector3::new(-1., -2., 3.));
+        assert_eq!(a.max(&b), Vector3::new(1., 0., 5.));
+        assert_eq!(a.abs(), Vector3::new(1., 2., 3.));
+        assert_eq!(-a, Vector3::new(-1., 2., -3.));
+        assert_eq!(Vector2::new(1, -2).abs(), Vector2::new(1, 2));
+        assert_eq!(a.lerp(&b, 0.5), Vector3::new(0., -1., 4.));
+
+        let mut c = a;
+        c -= b;
+        assert_eq!(c, Vector3::new(2., -2., -2.));
+        c *= 2.;
+        assert_eq!(c, Vector3::new(4., -4., -4.));
+        c /= 4.;
+        assert_eq!(c, Vector3::new(1., -1., -1.));
+
+        let mut d = Vector2::new(1., 2.);
+        d += Vector2::new(1., 1.);
+        d -= Vector2::new(0., 2.);
+        d *= 3.;
+        d /= 2.;
+        assert_eq!(d, Vector2::new(3., 1.5));
+    }
+
+    #[test]
+    fn test_reflect_and_refract() {
+        let normal = Vector3::new(0., 1., 0.);
+        let incoming = Vector3::new(1., -1., 0.).normalize();
+
+        assert_close(
+            incoming.reflect(&normal),
+            Vector3::new(1., 1., 0.).normalize(),
+        );
+
+        // Without a change of medium the direction doesn't change
+        assert_close(incoming.refract(&normal, 1.).unwrap(), incoming);
+
+        // Entering a denser medium bends towards the normal, leaving it at a grazing angle
+        // reflects everything
+        let refracted = incoming.refract(&normal, 1. / 1.5).unwrap();
+        assert!(refracted.x < incoming.x && refracted.y < 0.);
+        assert_eq!(incoming.refract(&normal, 1.5), None);
+    }
+
+    #[test]
+    fn test_vector4() {
+        let point = Vector4::from_vector3(Vector3::new(1., 2., 3.), 1.);
+        let translation = Matrix4::from_translation(Vector3::new(1., 0., 0.));
+
+        assert_eq!((translation * point).xyz(), Vector3::new(2., 2., 3.));
+        assert_eq!(
+            (translation * Vector4::new(1., 2., 3., 0.)).xyz(),
+            Vector3::new(1., 2., 3.)
+        );
+        assert_eq!((point * 2.).to_cartesian(), Vector3::new(1., 2., 3.));
+        assert_eq!(point * point, 15.);
+    }
+
     #[test]
     fn test_point_in_triangle() {
         let a = Vector2::new(2., 2.);
@@ -52,4 +168,204 @@ mod test {
         let baricentric = Vector2::new(4., 4.).get_barycentric_weights(&a, &b, &c);
         assert!(Vector2::is_in_triangle(&baricentric));
     }
+
+    fn assert_close(a: Vector3<f64>, b: Vector3<f64>) {
+        let diff = a - b;
+        assert!(diff * diff < 1e-18, "{a:?} != {b:?}");
+    }
+
+    fn assert_identity(matrix: Matrix4) {
+        for (row, values) in matrix.rows.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                let expected = if row == col { 1. } else { 0. };
+                assert!((value - expected).abs() < 1e-9, "{matrix:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_matrix3() {
+        let matrix = Matrix3::new([[2., 0., 1.], [1., 3., 0.], [0., 1., 4.]]);
+
+        assert_eq!(matrix.determinant(), 25.);
+        assert_eq!(matrix.transpose().column(0), Vector3::new(2., 0., 1.));
+
+        let inverse = matrix.inverse().expect("Matrix is invertible");
+        let product = Matrix4::from(matrix * inverse);
+        assert_identity(product);
+
+        let singular = Matrix3::new([[1., 2., 3.], [2., 4., 6.], [0., 1., 0.]]);
+        assert_eq!(singular.inverse(), None);
+    }
+
+    #[test]
+    fn test_matrix4() {
+        let matrix = Matrix4::new([
+            [2., 0., 1., 3.],
+            [1., 3., 0., -1.],
+            [0., 1., 4., 2.],
+            [1., 0., 0., 1.],
+        ]);
+
+        assert_eq!(matrix.transpose().transpose(), matrix);
+        assert_eq!(matrix.determinant(), matrix.transpose().determinant());
+        assert!((matrix.determinant() + 4.).abs() < 1e-9);
+
+        let inverse = matrix.inverse().expect("Matrix is invertible");
+        assert_identity(matrix * inverse);
+        assert_identity(inverse * matrix);
+
+        let translation = Matrix4::from_translation(Vector3::new(1., 2., 3.));
+        assert_eq!(
+            translation.transform_point(Vector3::new(1., 1., 1.)),
+            Vector3::new(2., 3., 4.)
+        );
+        assert_eq!(
+            translation.transform_vector(Vector3::new(1., 1., 1.)),
+            Vector3::new(1., 1., 1.)
+        );
+        assert_eq!(Matrix4::from_scale(Vector3::default()).inverse(), None);
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut transform = Transform::from_euler(0.4, -0.3, 1.2);
+        transform.scale = Vector3::new(2., -1., 0.5);
+        transform.position = Vector3::new(1., -2., 3.);
+
+        let point = Vector3::new(0.5, 1., -2.);
+        let matrix = transform.to_matrix();
+        assert_close(
+            matrix.transform_point(point),
+            transform.vertex_to_world(point),
+        );
+
+        // Yaw turns the x axis towards z
+        let yaw = Transform::new(std::f64::consts::FRAC_PI_2, 0.);
+        assert_close(
+            yaw.vertex_to_world(Vector3::new(1., 0., 0.)),
+            Vector3::new(0., 0., 1.),
+        );
+
+        // The parts are found back from the matrix
+        let decomposed = Transform::from_matrix(&matrix);
+        assert_close(
+            decomposed.to_matrix().transform_point(point),
+            matrix.transform_point(point),
+        );
+        assert_close(decomposed.position, transform.position);
+
+        // An axis scaled to nothing, as done to hide nodes, keeps a valid rotation
+        for rotated in [Transform::default(), Transform::from_euler(0.4, -0.3, 1.2)] {
+            let mut hidden = rotated;
+            hidden.scale = Vector3::new(2., 0., 0.5);
+            hidden.position = transform.position;
+            let decomposed = Transform::from_matrix(&hidden.to_matrix());
+            let rotation = decomposed.rotation;
+            assert!(
+                [rotation.w, rotation.x, rotation.y, rotation.z]
+                    .iter()
+                    .all(|c| c.is_finite())
+            );
+            assert_eq!(decomposed.scale.y, 0.);
+            assert_close(decomposed.position, transform.position);
+        }
+        let flat = Transform {
+            scale: Vector3::new(2., 0., 0.5),
+            ..Default::default()
+        };
+        let decomposed = Transform::from_matrix(&flat.to_matrix());
+        assert_close(
+            decomposed.to_matrix().transform_point(point),
+            flat.vertex_to_world(point),
+        );
+    }
+
+    #[test]
+    fn test_transform_hierarchy() {
+        let mut parent = Transform::new(0.7, 0.);
+        parent.position = Vector3::new(0., 0., -5.);
+        parent.scale = Vector3::new(2., 2., 2.);
+
+        let mut child = Transform::new(0., 0.2);
+        child.position = Vector3::new(1., 0., 0.);
+
+        let point = Vector3::new(0., 1., 0.);
+        let world = parent.to_matrix() * child.to_matrix();
+        assert_close(
+            world.transform_point(point),
+            parent.vertex_to_world(child.vertex_to_world(point)),
+        );
+    }
+
+    #[test]
+    fn test_quaternion_rotation() {
+        let quarter_turn = Quaternion::from_axis_angle(Vector3::new(0., 0., 2.), FRAC_PI_2);
+        assert_close(
+            quarter_turn.rotate(Vector3::new(1., 0., 0.)),
+            Vector3::new(0., 1., 0.),
+        );
+
+        let (axis, angle) = quarter_turn.to_axis_angle();
+        assert_close(axis, Vector3::new(0., 0., 1.));
+        assert!((angle - FRAC_PI_2).abs() < 1e-12);
+
+        // Multiplying applies the right rotation first
+        let tilt = Quaternion::from_axis_angle(Vector3::new(1., 0., 0.), 0.5);
+        let point = Vector3::new(0.3, -1., 2.);
+        assert_close(
+            (tilt * quarter_turn).rotate(point),
+            tilt.rotate(quarter_turn.rotate(point)),
+        );
+        assert_close(
+            (tilt * quarter_turn).to_matrix() * point,
+            tilt.to_matrix() * (quarter_turn.to_matrix() * point),
+        );
+        assert_close(
+            (quarter_turn.conjugate() * quarter_turn).rotate(point),
+            point,
+        );
+    }
+
+    #[test]
+    fn test_quaternion_conversions() {
+        let rotation = Quaternion::from_euler(2.5, -0.4, 1.1);
+        let point = Vector3::new(1., 2., 3.);
+
+        // The matrix conversion takes every branch for rotations of about 180 degrees
+        for q in [
+            rotation,
+            Quaternion::from_axis_angle(Vector3::new(1., 0.1, 0.), 3.1),
+            Quaternion::from_axis_angle(Vector3::new(0.1, 1., 0.), 3.1),
+            Quaternion::from_axis_angle(Vector3::new(0., 0.1, 1.), 3.1),
+        ] {
+            let converted = Quaternion::from_matrix(&q.to_matrix());
+            assert_close(converted.rotate(point), q.rotate(point));
+        }
+
+        let (yaw, pitch, roll) = rotation.to_euler();
+        assert!((yaw - 2.5).abs() < 1e-9);
+        assert!((pitch + 0.4).abs() < 1e-9);
+        assert!((roll - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_slerp() {
+        let start = Quaternion::IDENTITY;
+        let end = Quaternion::from_axis_angle(Vector3::new(0., 1., 0.), 2.);
+
+        assert_eq!(start.slerp(&end, 0.), start);
+        assert_close(
+            start.slerp(&end, 1.).rotate(Vector3::new(1., 0., 0.)),
+            end.rotate(Vector3::new(1., 0., 0.)),
+        );
+
+        // Halfway is half of the angle, also when going through the opposite quaternion
+        let (_, angle) = start.slerp(&end, 0.5).to_axis_angle();
+        assert!((angle - 1.).abs() < 1e-9);
+
+        let negated = Quaternion::new(-end.w, -end.x, -end.y, -end.z);
+        let (_, angle) = start.slerp(&negated, 0.25).to_axis_angle();
+        assert!((angle - 0.5).abs() < 1e-9);
+    }
 }
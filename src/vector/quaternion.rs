@@ -0,0 +1,225 @@
+use crate::vector::{matrix3::Matrix3, vector3::Vector3};
+
+use std::ops::Mul;
+
+/// Rotation stored as a unit quaternion `w + xi + yj + zk`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub const IDENTITY: Quaternion = Quaternion {
+        w: 1.,
+        x: 0.,
+        y: 0.,
+        z: 0.,
+    };
+
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Quaternion { w, x, y, z }
+    }
+
+    /// Counter clockwise rotation around an axis, looking from the side it points to. The axis
+    /// doesn't need to have length 1
+    pub fn from_axis_angle(axis: Vector3<f64>, angle: f64) -> Self {
+        let length = axis.length();
+        if length == 0. {
+            return Quaternion::IDENTITY;
+        }
+
+        let (sin, cos) = (angle / 2.).sin_cos();
+        let axis = axis * (sin / length);
+        Quaternion::new(cos, axis.x, axis.y, axis.z)
+    }
+
+    /// Axis and angle of the rotation, the axis is x when there is no rotation
+    pub fn to_axis_angle(&self) -> (Vector3<f64>, f64) {
+        let q = self.normalize();
+        let angle = 2. * q.w.clamp(-1., 1.).acos();
+        let sin = (1. - q.w * q.w).max(0.).sqrt();
+
+        if sin < 1e-12 {
+            return (Vector3::new(1., 0., 0.), angle);
+        }
+        (Vector3::new(q.x, q.y, q.z) / sin, angle)
+    }
+
+    /// Rotation with the same angles as `Transform`, applied as roll, then pitch, then yaw
+    pub fn from_euler(yaw: f64, pitch: f64, roll: f64) -> Self {
+        Quaternion::from_axis_angle(Vector3::new(0., 1., 0.), -yaw)
+            * Quaternion::from_axis_angle(Vector3::new(1., 0., 0.), -pitch)
+            * Quaternion::from_axis_angle(Vector3::new(0., 0., 1.), -roll)
+    }
+
+    /// Yaw, pitch and roll of the rotation, the inverse of `from_euler`
+    pub fn to_euler(&self) -> (f64, f64, f64) {
+        let r = self.to_matrix().rows;
+
+        (
+            -f64::atan2(r[0][2], r[2][2]),
+            r[1][2].clamp(-1., 1.).asin(),
+            -f64::atan2(r[1][0], r[1][1]),
+        )
+    }
+
+    /// Rotation of a matrix without scale or shear
+    pub fn from_matrix(matrix: &Matrix3) -> Self {
+        let m = matrix.rows;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        // Divide by the largest component to stay accurate
+        let q = if trace > 0. {
+            let s = (trace + 1.).sqrt() * 2.;
+            Quaternion::new(
+                s / 4.,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1. + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.;
+            Quaternion::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1. + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.;
+            Quaternion::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1. + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.;
+            Quaternion::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.,
+            )
+        };
+
+        q.normalize()
+    }
+
+    pub fn to_matrix(&self) -> Matrix3 {
+        let Quaternion { w, x, y, z } = *self;
+
+        Matrix3::new([
+            [
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y),
+            ],
+            [
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x),
+            ],
+            [
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y),
+            ],
+        ])
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Scales the quaternion to length 1, rounding errors slowly add up when multiplying them
+    pub fn normalize(&self) -> Self {
+        let length = self.length();
+        if length == 0. {
+            return Quaternion::IDENTITY;
+        }
+
+        Quaternion::new(
+            self.w / length,
+            self.x / length,
+            self.y / length,
+            self.z / length,
+        )
+    }
+
+    /// Opposite rotation, for unit quaternions
+    pub fn conjugate(&self) -> Self {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn rotate(&self, vector: Vector3<f64>) -> Vector3<f64> {
+        let axis = Vector3::new(self.x, self.y, self.z);
+        let t = axis.cross(&vector) * 2.;
+
+        vector + t * self.w + axis.cross(&t)
+    }
+
+    /// Rotation between `self` (`t` = 0) and `other` (`t` = 1), turning at constant speed along
+    /// the shortest path
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Self {
+        // q and -q are the same rotation, pick the closest one
+        let mut other = *other;
+        let mut cos = self.dot(&other);
+        if cos < 0. {
+            other = Quaternion::new(-other.w, -other.x, -other.y, -other.z);
+            cos = -cos;
+        }
+
+        // Almost parallel rotations are interpolated linearly, to avoid dividing by 0
+        let (a, b) = if cos > 0.9995 {
+            (1. - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1. - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        Quaternion::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        )
+        .normalize()
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::IDENTITY
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    /// Rotation applying `rhs` first and then `self`
+    fn mul(self, rhs: Self) -> Self::Output {
+        Quaternion::new(
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        )
+    }
+}
+
+impl Mul<Vector3<f64>> for Quaternion {
+    type Output = Vector3<f64>;
+
+    fn mul(self, rhs: Vector3<f64>) -> Self::Output {
+        self.rotate(rhs)
+    }
+}
@@ -1,51 +1,91 @@
-use super::vector3::Vector3;
+use super::{matrix3::Matrix3, matrix4::Matrix4, quaternion::Quaternion, vector3::Vector3};
 
-#[derive(Default)]
+/// Scales, rotates and then moves points
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Transform {
-    /// Rotation around y axis
-    pub yaw: f64,
-    /// Rotation around x axis
-    pub pitch: f64,
+    pub rotation: Quaternion,
     /// Position in world space
     pub position: Vector3<f64>,
+    /// Scale along each axis
+    pub scale: Vector3<f64>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::new(0., 0.)
+    }
 }
 
 impl Transform {
+    /// Rotation around the y axis (yaw) and then around the x axis (pitch)
     pub fn new(yaw: f64, pitch: f64) -> Self {
+        Transform::from_euler(yaw, pitch, 0.)
+    }
+
+    /// Rotation applied as roll (around z), then pitch (around x), then yaw (around y)
+    pub fn from_euler(yaw: f64, pitch: f64, roll: f64) -> Self {
         Transform {
-            yaw,
-            pitch,
+            rotation: Quaternion::from_euler(yaw, pitch, roll),
             position: Vector3::default(),
+            scale: Vector3::new(1., 1., 1.),
         }
     }
 
-    pub fn vertex_to_world(&self, p: Vector3<f64>) -> Vector3<f64> {
-        let (i, j, k) = self.get_basis_vectors();
-        Transform::apply_transform(i, j, k, p) + self.position
-    }
+    /// Splits a matrix made of a translation, a rotation and a scale back into its parts.
+    /// Shear can't be represented and is lost
+    pub fn from_matrix(matrix: &Matrix4) -> Self {
+        let linear = matrix.linear();
+        let [mut i, j, k] = [0, 1, 2].map(|idx| linear.column(idx));
 
-    fn get_basis_vectors(&self) -> (Vector3<f64>, Vector3<f64>, Vector3<f64>) {
-        let i_yaw = Vector3::new(f64::cos(self.yaw), 0., f64::sin(self.yaw));
-        let j_yaw = Vector3::new(0., 1., 0.);
-        let k_yaw = Vector3::new(-f64::sin(self.yaw), 0., f64::cos(self.yaw));
+        let mut scale = Vector3::new(i.length(), j.length(), k.length());
 
-        let i_pitch = Vector3::new(1., 0., 0.);
-        let j_pitch = Vector3::new(0., f64::cos(self.pitch), -f64::sin(self.pitch));
-        let k_pitch = Vector3::new(0., f64::sin(self.pitch), f64::cos(self.pitch));
+        // A mirrored basis is a rotation with a negative scale
+        if linear.determinant() < 0. {
+            scale.x = -scale.x;
+            i = -i;
+        }
 
-        let i = Transform::apply_transform(i_yaw, j_yaw, k_yaw, i_pitch);
-        let j = Transform::apply_transform(i_yaw, j_yaw, k_yaw, j_pitch);
-        let k = Transform::apply_transform(i_yaw, j_yaw, k_yaw, k_pitch);
+        // An axis scaled to nothing has no direction left, and keeps the unrotated one
+        let axis = |column: Vector3<f64>, length: f64, unrotated: Vector3<f64>| match length {
+            0. => unrotated,
+            _ => column / length,
+        };
+        let rotation = Matrix3::from_columns(
+            axis(i, scale.x.abs(), Vector3::new(1., 0., 0.)),
+            axis(j, scale.y, Vector3::new(0., 1., 0.)),
+            axis(k, scale.z, Vector3::new(0., 0., 1.)),
+        );
 
-        (i, j, k)
+        Transform {
+            rotation: Quaternion::from_matrix(&rotation),
+            position: matrix.translation(),
+            scale,
+        }
+    }
+
+    /// Yaw, pitch and roll of the rotation
+    pub fn euler(&self) -> (f64, f64, f64) {
+        self.rotation.to_euler()
+    }
+
+    /// Matrix applying the whole transform. Multiplying the matrix of a parent with the one of
+    /// its child places the child in the space of the parent
+    pub fn to_matrix(&self) -> Matrix4 {
+        Matrix4::from_translation(self.position)
+            * Matrix4::from(self.rotation.to_matrix() * Matrix3::from_scale(self.scale))
+    }
+
+    /// Largest factor any length is scaled by
+    pub fn max_scale(&self) -> f64 {
+        self.scale
+            .x
+            .abs()
+            .max(self.scale.y.abs())
+            .max(self.scale.z.abs())
     }
 
-    fn apply_transform(
-        i: Vector3<f64>,
-        j: Vector3<f64>,
-        k: Vector3<f64>,
-        p: Vector3<f64>,
-    ) -> Vector3<f64> {
-        i * p.x + j * p.y + k * p.z
+    pub fn vertex_to_world(&self, p: Vector3<f64>) -> Vector3<f64> {
+        let scaled = Vector3::new(p.x * self.scale.x, p.y * self.scale.y, p.z * self.scale.z);
+        self.rotation.rotate(scaled) + self.position
     }
 }
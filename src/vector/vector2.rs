@@ -3,10 +3,14 @@ use crate::vector::Vector;
 use std::clone::Clone;
 use std::default::Default;
 use std::ops::Add;
+use std::ops::AddAssign;
 use std::ops::Div;
+use std::ops::DivAssign;
 use std::ops::Mul;
+use std::ops::MulAssign;
 use std::ops::Neg;
 use std::ops::Sub;
+use std::ops::SubAssign;
 
 use super::vector3::Vector3;
 
@@ -69,14 +73,93 @@ where
     {
         Vector2::new(-self.y.clone(), self.x.clone())
     }
+
+    /// Z component of the cross product of the vectors extended to 3D, positive if `other` is
+    /// counter clockwise from `self`
+    pub fn cross(&self, other: &Vector2<T>) -> T {
+        self.x.clone() * other.y.clone() - self.y.clone() * other.x.clone()
+    }
+
+    pub fn length_squared(&self) -> T {
+        self.clone() * self.clone()
+    }
+
+    /// Smallest value of each component
+    pub fn min(&self, other: &Vector2<T>) -> Vector2<T> {
+        let min = |a: &T, b: &T| if b < a { b.clone() } else { a.clone() };
+        Vector2::new(min(&self.x, &other.x), min(&self.y, &other.y))
+    }
+
+    /// Largest value of each component
+    pub fn max(&self, other: &Vector2<T>) -> Vector2<T> {
+        let max = |a: &T, b: &T| if b > a { b.clone() } else { a.clone() };
+        Vector2::new(max(&self.x, &other.x), max(&self.y, &other.y))
+    }
+
+    /// Absolute value of each component
+    pub fn abs(&self) -> Vector2<T> {
+        let abs = |a: &T| {
+            if *a < T::default() {
+                -a.clone()
+            } else {
+                a.clone()
+            }
+        };
+        Vector2::new(abs(&self.x), abs(&self.y))
+    }
+}
+
+impl Vector2<f64> {
+    pub fn length(&self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    /// Vector with the same direction and length 1. The zero vector is returned as is
+    pub fn normalize(&self) -> Vector2<f64> {
+        let length = self.length();
+        if length == 0. { *self } else { *self / length }
+    }
+
+    pub fn distance(&self, other: &Vector2<f64>) -> f64 {
+        (*other - *self).length()
+    }
+
+    /// Linear interpolation, from `self` when `t` is 0 to `other` when `t` is 1
+    pub fn lerp(&self, other: &Vector2<f64>, t: f64) -> Vector2<f64> {
+        *self + (*other - *self) * t
+    }
+
+    /// Bounces the vector off a line with the given normal, which must have length 1
+    pub fn reflect(&self, normal: &Vector2<f64>) -> Vector2<f64> {
+        *self - *normal * (2. * (*self * *normal))
+    }
+
+    /// Bends the direction (of length 1) entering a surface with the given normal (of length 1),
+    /// where `eta` is the ratio between the refractive indices of the two sides. `None` on total
+    /// internal reflection
+    pub fn refract(&self, normal: &Vector2<f64>, eta: f64) -> Option<Vector2<f64>> {
+        let cos = *self * *normal;
+        let k = 1. - eta * eta * (1. - cos * cos);
+        if k < 0. {
+            return None;
+        }
+
+        Some(*self * eta - *normal * (eta * cos + k.sqrt()))
+    }
 }
 
-impl<T> Vector<T> for Vector2<T>
+impl<T> Vector<T, 2> for Vector2<T>
 where
     T: Clone,
 {
-    fn components(&self) -> Vec<&T> {
-        vec![&self.x, &self.y]
+    #[inline]
+    fn components(&self) -> [T; 2] {
+        [self.x.clone(), self.y.clone()]
+    }
+
+    #[inline]
+    fn from_components([x, y]: [T; 2]) -> Self {
+        Vector2 { x, y }
     }
 }
 
@@ -159,3 +242,78 @@ where
         Vector2::new(self.x + rhs.x, self.y + rhs.y)
     }
 }
+
+impl<T> AddAssign for Vector2<T>
+where
+    T: Sub<T, Output = T>
+        + Neg<Output = T>
+        + Clone
+        + Add<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    fn add_assign(&mut self, rhs: Self) {
+        *self = self.clone() + rhs
+    }
+}
+
+impl<T> SubAssign for Vector2<T>
+where
+    T: Sub<T, Output = T>
+        + Neg<Output = T>
+        + Clone
+        + Add<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = self.clone() - rhs
+    }
+}
+
+impl<T> MulAssign<T> for Vector2<T>
+where
+    T: Sub<T, Output = T>
+        + Neg<Output = T>
+        + Clone
+        + Add<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    fn mul_assign(&mut self, rhs: T) {
+        *self = self.clone() * rhs
+    }
+}
+
+impl<T> DivAssign<T> for Vector2<T>
+where
+    T: Sub<T, Output = T>
+        + Neg<Output = T>
+        + Clone
+        + Add<T, Output = T>
+        + Mul<T, Output = T>
+        + Div<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    fn div_assign(&mut self, rhs: T) {
+        *self = self.clone() / rhs
+    }
+}
+
+impl<T> Neg for Vector2<T>
+where
+    T: Clone + Neg<Output = T>,
+{
+    type Output = Vector2<T>;
+
+    fn neg(self) -> Self::Output {
+        Vector2 {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
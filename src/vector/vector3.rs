@@ -6,9 +6,12 @@ use std::default::Default;
 use std::ops::Add;
 use std::ops::AddAssign;
 use std::ops::Div;
+use std::ops::DivAssign;
 use std::ops::Mul;
+use std::ops::MulAssign;
 use std::ops::Neg;
 use std::ops::Sub;
+use std::ops::SubAssign;
 
 #[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct Vector3<T>
@@ -33,14 +36,104 @@ where
     pub fn new(x: T, y: T, z: T) -> Self {
         Vector3 { x, y, z }
     }
+
+    /// Vector perpendicular to both `self` and `other`, following the right hand rule
+    pub fn cross(&self, other: &Vector3<T>) -> Vector3<T> {
+        Vector3::new(
+            self.y.clone() * other.z.clone() - self.z.clone() * other.y.clone(),
+            self.z.clone() * other.x.clone() - self.x.clone() * other.z.clone(),
+            self.x.clone() * other.y.clone() - self.y.clone() * other.x.clone(),
+        )
+    }
+
+    pub fn length_squared(&self) -> T {
+        self.clone() * self.clone()
+    }
+
+    /// Smallest value of each component
+    pub fn min(&self, other: &Vector3<T>) -> Vector3<T> {
+        let min = |a: &T, b: &T| if b < a { b.clone() } else { a.clone() };
+        Vector3::new(
+            min(&self.x, &other.x),
+            min(&self.y, &other.y),
+            min(&self.z, &other.z),
+        )
+    }
+
+    /// Largest value of each component
+    pub fn max(&self, other: &Vector3<T>) -> Vector3<T> {
+        let max = |a: &T, b: &T| if b > a { b.clone() } else { a.clone() };
+        Vector3::new(
+            max(&self.x, &other.x),
+            max(&self.y, &other.y),
+            max(&self.z, &other.z),
+        )
+    }
+
+    /// Absolute value of each component
+    pub fn abs(&self) -> Vector3<T> {
+        let abs = |a: &T| {
+            if *a < T::default() {
+                -a.clone()
+            } else {
+                a.clone()
+            }
+        };
+        Vector3::new(abs(&self.x), abs(&self.y), abs(&self.z))
+    }
 }
 
-impl<T> Vector<T> for Vector3<T>
+impl Vector3<f64> {
+    pub fn length(&self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    /// Vector with the same direction and length 1. The zero vector is returned as is
+    pub fn normalize(&self) -> Vector3<f64> {
+        let length = self.length();
+        if length == 0. { *self } else { *self / length }
+    }
+
+    pub fn distance(&self, other: &Vector3<f64>) -> f64 {
+        (*other - *self).length()
+    }
+
+    /// Linear interpolation, from `self` when `t` is 0 to `other` when `t` is 1
+    pub fn lerp(&self, other: &Vector3<f64>, t: f64) -> Vector3<f64> {
+        *self + (*other - *self) * t
+    }
+
+    /// Bounces the vector off a surface with the given normal, which must have length 1
+    pub fn reflect(&self, normal: &Vector3<f64>) -> Vector3<f64> {
+        *self - *normal * (2. * (*self * *normal))
+    }
+
+    /// Bends the direction (of length 1) entering a surface with the given normal (of length 1),
+    /// where `eta` is the ratio between the refractive indices of the two sides. `None` on total
+    /// internal reflection
+    pub fn refract(&self, normal: &Vector3<f64>, eta: f64) -> Option<Vector3<f64>> {
+        let cos = *self * *normal;
+        let k = 1. - eta * eta * (1. - cos * cos);
+        if k < 0. {
+            return None;
+        }
+
+        Some(*self * eta - *normal * (eta * cos + k.sqrt()))
+    }
+}
+
+impl<T> Vector<T, 3> for Vector3<T>
 where
     T: Clone,
 {
-    fn components(&self) -> Vec<&T> {
-        vec![&self.x, &self.y, &self.z]
+    #[inline]
+    fn components(&self) -> [T; 3] {
+        [self.x.clone(), self.y.clone(), self.z.clone()]
+    }
+
+    #[inline]
+    fn from_components([x, y, z]: [T; 3]) -> Self {
+        Vector3 { x, y, z }
     }
 }
 
@@ -147,6 +240,67 @@ where
     }
 }
 
+impl<T> SubAssign for Vector3<T>
+where
+    T: Clone
+        + Add<T, Output = T>
+        + Neg<Output = T>
+        + Sub<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = self.clone() - rhs
+    }
+}
+
+impl<T> MulAssign<T> for Vector3<T>
+where
+    T: Clone
+        + Add<T, Output = T>
+        + Neg<Output = T>
+        + Sub<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    fn mul_assign(&mut self, rhs: T) {
+        *self = self.clone() * rhs
+    }
+}
+
+impl<T> DivAssign<T> for Vector3<T>
+where
+    T: Clone
+        + Add<T, Output = T>
+        + Neg<Output = T>
+        + Sub<T, Output = T>
+        + Mul<T, Output = T>
+        + Div<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    fn div_assign(&mut self, rhs: T) {
+        *self = self.clone() / rhs
+    }
+}
+
+impl<T> Neg for Vector3<T>
+where
+    T: Clone + Neg<Output = T>,
+{
+    type Output = Vector3<T>;
+
+    fn neg(self) -> Self::Output {
+        Vector3 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
 impl<T> Into<Vector2<T>> for Vector3<T>
 where
     T: Clone
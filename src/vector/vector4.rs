@@ -0,0 +1,189 @@
+use crate::vector::Vector;
+use crate::vector::vector3::Vector3;
+
+use std::clone::Clone;
+use std::default::Default;
+use std::ops::Add;
+use std::ops::Div;
+use std::ops::Mul;
+use std::ops::Neg;
+use std::ops::Sub;
+
+/// Homogeneous coordinates, points have `w` = 1 and directions `w` = 0
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Vector4<T>
+where
+    T: Clone,
+{
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+impl<T> Vector4<T>
+where
+    T: Neg<Output = T>
+        + Clone
+        + Sub<T, Output = T>
+        + Add<T, Output = T>
+        + Mul<T, Output = T>
+        + Default
+        + PartialOrd,
+{
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
+        Vector4 { x, y, z, w }
+    }
+
+    pub fn from_vector3(vector: Vector3<T>, w: T) -> Self {
+        Vector4::new(vector.x, vector.y, vector.z, w)
+    }
+
+    /// First three components, ignoring `w`
+    pub fn xyz(&self) -> Vector3<T> {
+        Vector3::new(self.x.clone(), self.y.clone(), self.z.clone())
+    }
+}
+
+impl Vector4<f64> {
+    /// Cartesian point represented by the coordinates, dividing by `w`
+    pub fn to_cartesian(&self) -> Vector3<f64> {
+        self.xyz() / self.w
+    }
+}
+
+impl<T> Vector<T, 4> for Vector4<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn components(&self) -> [T; 4] {
+        [
+            self.x.clone(),
+            self.y.clone(),
+            self.z.clone(),
+            self.w.clone(),
+        ]
+    }
+
+    #[inline]
+    fn from_components([x, y, z, w]: [T; 4]) -> Self {
+        Vector4 { x, y, z, w }
+    }
+}
+
+impl<T> Mul for Vector4<T>
+where
+    T: Clone + Mul<T, Output = T> + Add<T, Output = T> + Default,
+{
+    type Output = T;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.dot(rhs)
+    }
+}
+
+impl<T> Mul<T> for Vector4<T>
+where
+    T: Clone
+        + Mul<T, Output = T>
+        + Neg<Output = T>
+        + Add<T, Output = T>
+        + Default
+        + PartialOrd
+        + Sub<T, Output = T>,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Vector4::new(
+            self.x * rhs.clone(),
+            self.y * rhs.clone(),
+            self.z * rhs.clone(),
+            self.w * rhs,
+        )
+    }
+}
+
+impl<T> Div<T> for Vector4<T>
+where
+    T: Clone
+        + Div<T, Output = T>
+        + Neg<Output = T>
+        + Add<T, Output = T>
+        + Sub<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Vector4::new(
+            self.x / rhs.clone(),
+            self.y / rhs.clone(),
+            self.z / rhs.clone(),
+            self.w / rhs,
+        )
+    }
+}
+
+impl<T> Sub for Vector4<T>
+where
+    T: Clone
+        + Sub<T, Output = T>
+        + Neg<Output = T>
+        + Add<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    type Output = Vector4<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector4::new(
+            self.x - rhs.x,
+            self.y - rhs.y,
+            self.z - rhs.z,
+            self.w - rhs.w,
+        )
+    }
+}
+
+impl<T> Add for Vector4<T>
+where
+    T: Clone
+        + Add<T, Output = T>
+        + Neg<Output = T>
+        + Sub<T, Output = T>
+        + Mul<T, Output = T>
+        + PartialOrd
+        + Default,
+{
+    type Output = Vector4<T>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Vector4::new(
+            self.x + rhs.x,
+            self.y + rhs.y,
+            self.z + rhs.z,
+            self.w + rhs.w,
+        )
+    }
+}
+
+impl<T> Neg for Vector4<T>
+where
+    T: Clone + Neg<Output = T>,
+{
+    type Output = Vector4<T>;
+
+    fn neg(self) -> Self::Output {
+        Vector4 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: -self.w,
+        }
+    }
+}